@@ -0,0 +1,217 @@
+/// The symbols a table declared with [`api!`](crate::api) could not resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSymbols {
+    pub table: &'static str,
+    pub symbols: Vec<&'static str>,
+}
+
+impl std::fmt::Display for MissingSymbols {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is missing symbol(s): ", self.table)?;
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{}`", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingSymbols {}
+
+/// Declares a struct of typed function pointers that is loaded in one go.
+///
+/// Every entry names the symbol it is resolved from. Entries of type
+/// `Option<T>` are optional and left as `None` when the symbol is missing; any
+/// other missing entry makes loading fail with [`MissingSymbols`] listing all
+/// of them.
+///
+/// ```no_run
+/// dynamic_library::api! {
+///     pub struct LibM {
+///         pub cos: extern "C" fn(f64) -> f64 = "cos",
+///         pub cospi: Option<extern "C" fn(f64) -> f64> = "cospi",
+///     }
+/// }
+///
+/// let lib = dynamic_library::DynamicLibrary::new("libm.so.6").unwrap();
+/// // SAFETY: The declared types match the C declarations.
+/// let libm = unsafe { LibM::load(&lib) }.unwrap();
+/// assert_eq!((libm.cos)(0.0), 1.0);
+/// ```
+///
+/// The generated `load` and `load_with` functions are `unsafe`, because the
+/// declared types cannot be checked against the symbols. Function pointers
+/// copied out of the table must not be called after the library is unloaded.
+#[macro_export]
+macro_rules! api {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::api!(@parse [$(#[$meta])*] [$vis] $name [] $($body)*);
+    };
+
+    (@parse $meta:tt $vis:tt $name:ident [$($fields:tt)*]
+        $(#[$field_meta:meta])*
+        $field_vis:vis $field:ident : Option<$ty:ty> = $symbol:literal
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::api!(@parse $meta $vis $name [
+            $($fields)*
+            { [$(#[$field_meta])*] [$field_vis] $field [$ty] $symbol optional }
+        ] $($($rest)*)?);
+    };
+
+    (@parse $meta:tt $vis:tt $name:ident [$($fields:tt)*]
+        $(#[$field_meta:meta])*
+        $field_vis:vis $field:ident : $ty:ty = $symbol:literal
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::api!(@parse $meta $vis $name [
+            $($fields)*
+            { [$(#[$field_meta])*] [$field_vis] $field [$ty] $symbol required }
+        ] $($($rest)*)?);
+    };
+
+    (@parse [$(#[$meta:meta])*] [$vis:vis] $name:ident [$({
+        [$(#[$field_meta:meta])*] [$field_vis:vis] $field:ident [$ty:ty] $symbol:literal $kind:ident
+    })*]) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $crate::api!(@type $kind $ty),
+            )*
+        }
+
+        impl $name {
+            /// Resolves every entry from `lib`.
+            ///
+            /// # Safety
+            ///
+            /// The declared types must match the symbols.
+            #[allow(dead_code)]
+            $vis unsafe fn load(
+                lib: &$crate::DynamicLibrary,
+            ) -> ::std::result::Result<Self, $crate::MissingSymbols> {
+                Self::load_with(|name| lib.get(name.to_str().ok()?).ok())
+            }
+
+            /// Resolves every entry through `f`, which returns the address of
+            /// the given symbol or `None` if it does not exist.
+            ///
+            /// # Safety
+            ///
+            /// The declared types must match the symbols.
+            #[allow(dead_code)]
+            $vis unsafe fn load_with<F>(
+                mut f: F,
+            ) -> ::std::result::Result<Self, $crate::MissingSymbols>
+            where
+                F: FnMut(&::std::ffi::CStr) -> ::std::option::Option<::std::ptr::NonNull<()>>,
+            {
+                let mut missing = ::std::vec::Vec::new();
+                $(
+                    let $field = $crate::api!(@resolve f [$ty] $symbol);
+                    $crate::api!(@check $kind $field missing $symbol);
+                )*
+
+                if !missing.is_empty() {
+                    return ::std::result::Result::Err($crate::MissingSymbols {
+                        table: stringify!($name),
+                        symbols: missing,
+                    });
+                }
+
+                ::std::result::Result::Ok(Self {
+                    $($field: $crate::api!(@unwrap $kind $field),)*
+                })
+            }
+        }
+    };
+
+    (@type required $ty:ty) => { $ty };
+    (@type optional $ty:ty) => { ::std::option::Option<$ty> };
+
+    (@resolve $f:ident [$ty:ty] $symbol:literal) => {{
+        const _: () = assert!(
+            ::std::mem::size_of::<$ty>() == ::std::mem::size_of::<*mut ()>(),
+            concat!("Type of `", $symbol, "` must be pointer-sized"),
+        );
+
+        let name = ::std::ffi::CStr::from_bytes_with_nul(concat!($symbol, "\0").as_bytes())
+            .expect(concat!("`", $symbol, "` contains a nul byte"));
+        $f(name).map(|p| ::std::mem::transmute_copy::<*mut (), $ty>(&p.as_ptr()))
+    }};
+
+    (@check required $field:ident $missing:ident $symbol:literal) => {
+        if $field.is_none() {
+            $missing.push($symbol);
+        }
+    };
+    (@check optional $field:ident $missing:ident $symbol:literal) => {};
+
+    (@unwrap required $field:ident) => {
+        match $field {
+            ::std::option::Option::Some(f) => f,
+            ::std::option::Option::None => unreachable!(),
+        }
+    };
+    (@unwrap optional $field:ident) => {
+        $field
+    };
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use crate::{DynamicLibrary, MissingSymbols};
+
+    type UnaryFn = extern "C" fn(f64) -> f64;
+
+    crate::api! {
+        // Never loads, so its fields are never read.
+        #[allow(dead_code)]
+        struct Broken {
+            cos: UnaryFn = "cos",
+            first: UnaryFn = "tanelorn_missing_first",
+            second: UnaryFn = "tanelorn_missing_second",
+            optional: Option<UnaryFn> = "tanelorn_missing_optional",
+        }
+    }
+
+    crate::api! {
+        struct Partial {
+            cos: UnaryFn = "cos",
+            optional: Option<UnaryFn> = "tanelorn_missing_optional",
+        }
+    }
+
+    #[test]
+    fn reports_every_missing_required_symbol() {
+        let lib = DynamicLibrary::new("libm.so.6").unwrap();
+        // SAFETY: `cos` has this signature in libm, the others do not exist.
+        let e = unsafe { Broken::load(&lib) }.err().unwrap();
+
+        assert_eq!(
+            e,
+            MissingSymbols {
+                table: "Broken",
+                symbols: vec!["tanelorn_missing_first", "tanelorn_missing_second"],
+            }
+        );
+    }
+
+    #[test]
+    fn leaves_missing_optional_symbol_empty() {
+        let lib = DynamicLibrary::new("libm.so.6").unwrap();
+        // SAFETY: `cos` has this signature in libm, the other does not exist.
+        let partial = unsafe { Partial::load(&lib) }.unwrap();
+
+        assert!(partial.optional.is_none());
+        assert_eq!((partial.cos)(0.0), 1.0);
+    }
+}
@@ -1,20 +1,271 @@
+mod api;
+pub mod plugin;
+mod search;
 mod sys;
 
 use crate::sys as lib_impl;
 
-use std::path::Path;
+pub use crate::api::MissingSymbols;
+pub use crate::plugin::{Plugin, PluginRegistry};
+pub use crate::search::Search;
+pub use crate::sys::LoadOptions;
+
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The library at `path` could not be loaded.
+    Load { path: PathBuf, message: String },
+    /// The symbol `name` could not be found in a loaded library.
+    Symbol { name: String, message: String },
+    /// None of the candidates of a [`Search`] could be loaded.
+    Search { attempts: Vec<Error> },
+    /// The library at `path` is not a compatible plugin.
+    Plugin { path: PathBuf, message: String },
+    /// The loader could not be queried about loaded libraries.
+    Introspect { message: String },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load { path, message } => {
+                write!(f, "Could not load `{}`: {}", path.display(), message)
+            }
+            Self::Symbol { name, message } => {
+                write!(f, "Could not retrieve symbol `{}`: {}", name, message)
+            }
+            Self::Search { attempts } if attempts.is_empty() => {
+                write!(f, "Could not load library: no candidates were given")
+            }
+            Self::Search { attempts } => {
+                write!(f, "Could not load library from any candidate:")?;
+                for attempt in attempts {
+                    write!(f, "\n  {}", attempt)?;
+                }
+                Ok(())
+            }
+            Self::Plugin { path, message } => {
+                write!(f, "Could not load plugin `{}`: {}", path.display(), message)
+            }
+            Self::Introspect { message } => {
+                write!(f, "Could not query loaded libraries: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
 pub struct DynamicLibrary(lib_impl::DynamicLibrary);
 
 impl DynamicLibrary {
-    pub fn new<P: AsRef<Path>>(path: P) -> Option<Self> {
-        lib_impl::DynamicLibrary::new(path).map(Self)
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        LoadOptions::default().open(path)
+    }
+
+    /// Returns a handle to the running executable. Symbols it exports, or that
+    /// were linked into it statically, can be looked up through this handle.
+    pub fn this_process() -> Result<Self> {
+        lib_impl::DynamicLibrary::this_process().map(Self)
+    }
+
+    /// Returns a handle to `path` only if it is already loaded in this process.
+    /// The library is never loaded as a side effect.
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        lib_impl::DynamicLibrary::open_existing(path).map(Self)
+    }
+
+    /// The path the library was actually loaded from, after the system search.
+    pub fn path(&self) -> Result<PathBuf> {
+        self.0.path()
+    }
+
+    /// Names of the symbols the library exports.
+    pub fn symbols(&self) -> Result<Vec<String>> {
+        self.0.symbols()
     }
 
-    pub fn get<T: AsRef<str>>(&self, proc: T) -> Option<NonNull<()>> {
+    pub fn get<T: AsRef<str>>(&self, proc: T) -> Result<NonNull<()>> {
         self.0
             .get(proc)
             .map(|p| unsafe { NonNull::new_unchecked(p as _) })
     }
+
+    /// Looks up `proc` and reinterprets it as `T`, usually a function pointer.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be a pointer-sized type matching the actual type of the exported
+    /// symbol, e.g. an `extern "C" fn` with the correct signature. Types of
+    /// another size are rejected at compile time:
+    ///
+    /// ```compile_fail
+    /// # let lib = dynamic_library::DynamicLibrary::this_process().unwrap();
+    /// let _ = unsafe { lib.get_typed::<[u8; 3]>("malloc") };
+    /// ```
+    pub unsafe fn get_typed<T: Copy>(&self, proc: impl AsRef<str>) -> Result<Symbol<'_, T>> {
+        const {
+            assert!(
+                std::mem::size_of::<T>() == std::mem::size_of::<*mut ()>(),
+                "Symbol type must be pointer-sized"
+            )
+        };
+
+        self.get(proc).map(|p| Symbol {
+            inner: std::mem::transmute_copy(&p.as_ptr()),
+            _lib: PhantomData,
+        })
+    }
+}
+
+/// Paths of every library currently loaded in the process, including the
+/// executable itself.
+pub fn loaded_modules() -> Result<Vec<PathBuf>> {
+    lib_impl::loaded_modules()
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<DynamicLibrary> {
+        lib_impl::DynamicLibrary::new(path, self).map(DynamicLibrary)
+    }
+}
+
+/// A typed symbol that cannot outlive the [`DynamicLibrary`] it was loaded from.
+#[derive(Clone, Copy)]
+pub struct Symbol<'lib, T> {
+    inner: T,
+    _lib: PhantomData<&'lib DynamicLibrary>,
+}
+
+impl<T> Deref for Symbol<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> std::fmt::Debug for Symbol<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Symbol")
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    const LIBM: &str = "libm.so.6";
+
+    #[test]
+    fn calls_resolved_symbol() {
+        let lib = DynamicLibrary::new(LIBM).unwrap();
+        assert!(lib.get("cos").is_ok());
+
+        // SAFETY: `cos` has this signature in libm.
+        let cos = unsafe { lib.get_typed::<extern "C" fn(f64) -> f64>("cos") }.unwrap();
+        assert_eq!(cos(0.0), 1.0);
+    }
+
+    #[test]
+    fn missing_library_carries_dlerror() {
+        let path = "libtanelorn-does-not-exist.so";
+        match DynamicLibrary::new(path) {
+            Err(Error::Load { path: p, message }) => {
+                assert_eq!(p, Path::new(path));
+                assert!(message.contains(path), "{}", message);
+            }
+            other => panic!("Expected a load error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn missing_symbol_carries_dlerror() {
+        let lib = DynamicLibrary::new(LIBM).unwrap();
+        let name = "tanelorn_does_not_exist";
+        match lib.get(name) {
+            Err(Error::Symbol { name: n, message }) => {
+                assert_eq!(n, name);
+                assert!(message.contains(name), "{}", message);
+            }
+            other => panic!("Expected a symbol error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn opens_existing_library_only_if_loaded() {
+        let _lib = DynamicLibrary::new(LIBM).unwrap();
+        let existing = DynamicLibrary::open_existing(LIBM).unwrap();
+        assert!(existing.get("cos").is_ok());
+
+        let path = "libtanelorn-not-loaded.so";
+        match DynamicLibrary::open_existing(path) {
+            Err(Error::Load { path: p, .. }) => assert_eq!(p, Path::new(path)),
+            other => panic!("Expected a load error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn this_process_resolves_libc() {
+        let process = DynamicLibrary::this_process().unwrap();
+
+        assert!(process.get("malloc").is_ok());
+    }
+
+    #[cfg(target_env = "gnu")]
+    #[test]
+    fn reports_path_after_search() {
+        let lib = DynamicLibrary::new(LIBM).unwrap();
+        let path = lib.path().unwrap();
+
+        assert!(path.ends_with(LIBM), "{}", path.display());
+        assert!(path.is_absolute(), "{}", path.display());
+    }
+
+    #[cfg(target_env = "gnu")]
+    #[test]
+    fn lists_exported_symbols_only() {
+        let lib = DynamicLibrary::new(LIBM).unwrap();
+        let symbols = lib.symbols().unwrap();
+
+        for exported in ["cos", "sin", "sqrt"] {
+            assert!(
+                symbols.iter().any(|s| s == exported),
+                "missing `{}`",
+                exported
+            );
+        }
+        // Imported from libc, so undefined in libm.
+        for imported in ["__assert_fail", "__stack_chk_fail", "fputs", "qsort"] {
+            assert!(
+                !symbols.iter().any(|s| s == imported),
+                "lists import `{}`",
+                imported
+            );
+        }
+    }
+
+    #[cfg(target_env = "gnu")]
+    #[test]
+    fn loaded_modules_exist_on_disk() {
+        let _lib = DynamicLibrary::new(LIBM).unwrap();
+        let modules = loaded_modules().unwrap();
+
+        assert!(modules.iter().any(|m| m.ends_with(LIBM)), "{:?}", modules);
+        for module in &modules {
+            assert!(module.exists(), "`{}` does not exist", module.display());
+        }
+    }
 }
@@ -0,0 +1,308 @@
+//! A versioned ABI that libraries implement to be loaded as plugins.
+//!
+//! A plugin exports a [`PluginDescriptor`] under the symbol
+//! [`DESCRIPTOR_SYMBOL`], usually through [`export_plugin!`](crate::export_plugin):
+//!
+//! ```
+//! dynamic_library::export_plugin! {
+//!     name: "physics",
+//!     capabilities: ["update", "debug-draw"],
+//! }
+//! ```
+
+use crate::{DynamicLibrary, Error, Result};
+
+use std::ffi::{c_char, CStr};
+use std::path::{Path, PathBuf};
+
+/// Version of [`PluginDescriptor`]. Bumped on every incompatible change.
+pub const ABI_VERSION: u32 = 1;
+
+pub const DESCRIPTOR_SYMBOL: &str = "TANELORN_PLUGIN";
+
+/// Describes a plugin to the loader.
+///
+/// `abi_version` is the first field in every version of the ABI, so that a
+/// loader can always read it before interpreting the rest.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginDescriptor {
+    pub abi_version: u32,
+    /// Null-terminated UTF-8 name.
+    pub name: *const c_char,
+    /// `capability_count` null-terminated UTF-8 strings.
+    pub capabilities: *const *const c_char,
+    pub capability_count: usize,
+}
+
+// SAFETY: Descriptors are immutable statics pointing at string literals.
+unsafe impl Sync for PluginDescriptor {}
+
+/// Exports a [`PluginDescriptor`] for the current crate under [`DESCRIPTOR_SYMBOL`].
+#[macro_export]
+macro_rules! export_plugin {
+    (name: $name:literal, capabilities: [$($capability:literal),* $(,)?] $(,)?) => {
+        #[no_mangle]
+        pub static TANELORN_PLUGIN: $crate::plugin::PluginDescriptor = {
+            const CAPABILITIES: &[*const ::std::ffi::c_char] =
+                &[$(concat!($capability, "\0").as_ptr().cast()),*];
+
+            $crate::plugin::PluginDescriptor {
+                abi_version: $crate::plugin::ABI_VERSION,
+                name: concat!($name, "\0").as_ptr().cast(),
+                capabilities: CAPABILITIES.as_ptr(),
+                capability_count: CAPABILITIES.len(),
+            }
+        };
+    };
+}
+
+/// A library whose descriptor was found and is compatible with this loader.
+pub struct Plugin {
+    name: String,
+    capabilities: Vec<String>,
+    path: PathBuf,
+    lib: DynamicLibrary,
+}
+
+impl Plugin {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        Self::new(DynamicLibrary::new(path)?, path)
+    }
+
+    /// Validates the descriptor of an already loaded library. `path` is only
+    /// used to identify the plugin in errors and diagnostics.
+    pub fn new<P: Into<PathBuf>>(lib: DynamicLibrary, path: P) -> Result<Self> {
+        let path = path.into();
+        let descriptor = lib.get(DESCRIPTOR_SYMBOL).map_err(|_| Error::Plugin {
+            path: path.clone(),
+            message: format!("missing `{}` descriptor", DESCRIPTOR_SYMBOL),
+        })?;
+        // SAFETY: `DESCRIPTOR_SYMBOL` is reserved for `PluginDescriptor`.
+        let (name, capabilities) = unsafe { read_descriptor(descriptor.as_ptr(), &path)? };
+
+        Ok(Self {
+            name,
+            capabilities,
+            path,
+            lib,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn capabilities(&self) -> &[String] {
+        &self.capabilities
+    }
+
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn library(&self) -> &DynamicLibrary {
+        &self.lib
+    }
+}
+
+impl std::fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Plugin")
+            .field("name", &self.name)
+            .field("capabilities", &self.capabilities)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// The set of loaded plugins, in load order. Plugin names are unique.
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+    plugins: Vec<Plugin>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<&Plugin> {
+        self.insert(Plugin::load(path)?)
+    }
+
+    pub fn insert(&mut self, plugin: Plugin) -> Result<&Plugin> {
+        if let Some(existing) = self.get(plugin.name()) {
+            return Err(Error::Plugin {
+                path: plugin.path,
+                message: format!(
+                    "a plugin named `{}` is already loaded from `{}`",
+                    existing.name,
+                    existing.path.display()
+                ),
+            });
+        }
+
+        self.plugins.push(plugin);
+        Ok(&self.plugins[self.plugins.len() - 1])
+    }
+
+    /// Removes the plugin called `name`, unloading its library once the
+    /// returned value is dropped.
+    pub fn remove(&mut self, name: &str) -> Option<Plugin> {
+        let index = self.plugins.iter().position(|p| p.name == name)?;
+        Some(self.plugins.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Plugin> {
+        self.plugins.iter().find(|p| p.name == name)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Plugin> {
+        self.plugins.iter()
+    }
+
+    pub fn with_capability<'a>(&'a self, capability: &'a str) -> impl Iterator<Item = &'a Plugin> {
+        self.plugins
+            .iter()
+            .filter(move |p| p.has_capability(capability))
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+}
+
+/// Validates a descriptor and reads its name and capabilities. `path` is only
+/// used in errors.
+///
+/// # Safety
+///
+/// `descriptor` must point to a `u32` ABI version, followed by the rest of a
+/// [`PluginDescriptor`] if it is [`ABI_VERSION`].
+unsafe fn read_descriptor(descriptor: *const (), path: &Path) -> Result<(String, Vec<String>)> {
+    let error = |message: String| Error::Plugin {
+        path: path.into(),
+        message,
+    };
+
+    // Only the version is read until it is known to match, since descriptors
+    // of other versions may be smaller than this one.
+    let abi_version = descriptor.cast::<u32>().read();
+    if abi_version != ABI_VERSION {
+        return Err(error(format!(
+            "ABI version {} is not supported, expected {}",
+            abi_version, ABI_VERSION
+        )));
+    }
+
+    // The ABI version matches, so the remaining fields have the layout and
+    // meaning documented on `PluginDescriptor`.
+    let descriptor = &*descriptor.cast::<PluginDescriptor>();
+    let name =
+        read_str(descriptor.name).ok_or_else(|| error("name is null or not valid UTF-8".into()))?;
+    if name.is_empty() {
+        return Err(error("name is empty".into()));
+    }
+
+    let capabilities = if descriptor.capability_count == 0 {
+        Vec::new()
+    } else if descriptor.capabilities.is_null() {
+        return Err(error("capability list is null".into()));
+    } else {
+        std::slice::from_raw_parts(descriptor.capabilities, descriptor.capability_count)
+            .iter()
+            .map(|&c| read_str(c))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| error("capability is null or not valid UTF-8".into()))?
+    };
+
+    Ok((name, capabilities))
+}
+
+/// # Safety
+///
+/// `ptr` must be null or point to a null-terminated string.
+unsafe fn read_str(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(ptr).to_str().ok().map(Into::into)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    crate::export_plugin! {
+        name: "test",
+        capabilities: ["update", "debug-draw"],
+    }
+
+    const LIBM: &str = "libm.so.6";
+
+    fn plugin_error(result: Result<impl std::fmt::Debug>) -> String {
+        match result {
+            Err(Error::Plugin { message, .. }) => message,
+            other => panic!("Expected a plugin error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_exported_descriptor() {
+        let descriptor = &TANELORN_PLUGIN as *const PluginDescriptor as *const ();
+        let (name, capabilities) =
+            unsafe { read_descriptor(descriptor, Path::new("test")) }.unwrap();
+
+        assert_eq!(name, "test");
+        assert_eq!(capabilities, ["update", "debug-draw"]);
+    }
+
+    #[test]
+    fn rejects_other_abi_version() {
+        // Smaller than a `PluginDescriptor`, so only the version may be read.
+        static FUTURE: u32 = ABI_VERSION + 1;
+        let descriptor = &FUTURE as *const u32 as *const ();
+        let message = plugin_error(unsafe { read_descriptor(descriptor, Path::new("future")) });
+
+        let expected = format!("ABI version {}", ABI_VERSION + 1);
+        assert!(message.contains(&expected), "{}", message);
+    }
+
+    #[test]
+    fn rejects_missing_descriptor() {
+        let message = plugin_error(Plugin::load(LIBM));
+
+        assert!(message.contains(DESCRIPTOR_SYMBOL), "{}", message);
+    }
+
+    #[test]
+    fn rejects_duplicate_name() {
+        let plugin = |path: &str| Plugin {
+            name: "test".into(),
+            capabilities: Vec::new(),
+            path: path.into(),
+            lib: DynamicLibrary::new(LIBM).unwrap(),
+        };
+        let mut registry = PluginRegistry::new();
+        registry.insert(plugin("first")).unwrap();
+        let message = plugin_error(registry.insert(plugin("second")));
+
+        assert!(
+            message.contains("already loaded from `first`"),
+            "{}",
+            message
+        );
+        assert_eq!(registry.len(), 1);
+    }
+}
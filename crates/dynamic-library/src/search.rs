@@ -0,0 +1,155 @@
+use crate::{DynamicLibrary, Error, LoadOptions, Result};
+
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+/// An ordered list of candidate paths tried one after another until a library
+/// loads.
+///
+/// ```no_run
+/// use dynamic_library::Search;
+///
+/// let lib = Search::new()
+///     .env_var("TANELORN_VULKAN_LOADER")
+///     .candidate("libvulkan.so.1")
+///     .candidate("libvulkan.so")
+///     .open();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    candidates: Vec<PathBuf>,
+    options: LoadOptions,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a single file name or path.
+    pub fn candidate<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.candidates.push(path.into());
+        self
+    }
+
+    /// Appends every path in `paths`, preserving their order.
+    pub fn candidates<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.candidates.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Appends the paths listed in the environment variable `key`, separated the
+    /// same way as `PATH`. Does nothing if the variable is not set.
+    pub fn env_var<K: AsRef<OsStr>>(mut self, key: K) -> Self {
+        if let Some(value) = std::env::var_os(key) {
+            self.candidates
+                .extend(std::env::split_paths(&value).filter(|p| !p.as_os_str().is_empty()));
+        }
+        self
+    }
+
+    /// Options used to load every candidate.
+    pub fn options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.candidates
+    }
+
+    /// Loads the first candidate that succeeds. If none does, the returned
+    /// [`Error::Search`] holds the failure of every attempt in order.
+    pub fn open(&self) -> Result<DynamicLibrary> {
+        let mut attempts = Vec::with_capacity(self.candidates.len());
+        for path in &self.candidates {
+            match self.options.open(path) {
+                Ok(lib) => return Ok(lib),
+                Err(e) => attempts.push(e),
+            }
+        }
+
+        Err(Error::Search { attempts })
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    const LIBM: &str = "libm.so.6";
+
+    /// The path of every failed attempt, in order.
+    fn attempted_paths(result: Result<DynamicLibrary>) -> Vec<PathBuf> {
+        match result {
+            Err(Error::Search { attempts }) => attempts
+                .into_iter()
+                .map(|attempt| match attempt {
+                    Error::Load { path, .. } => path,
+                    other => panic!("Expected a load error, got {:?}", other),
+                })
+                .collect(),
+            other => panic!("Expected a search error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn tries_candidates_in_order() {
+        let search = Search::new()
+            .candidate("libtanelorn-missing-a.so")
+            .candidate(LIBM)
+            .candidate("libtanelorn-missing-b.so");
+        let lib = search.open().unwrap();
+
+        assert!(lib.path().unwrap().ends_with(LIBM));
+    }
+
+    #[test]
+    fn reports_every_attempt() {
+        let search =
+            Search::new().candidates(["libtanelorn-missing-a.so", "libtanelorn-missing-b.so"]);
+
+        assert_eq!(
+            attempted_paths(search.open()),
+            [
+                Path::new("libtanelorn-missing-a.so"),
+                Path::new("libtanelorn-missing-b.so")
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_empty_search() {
+        let message = Search::new().open().err().unwrap().to_string();
+
+        assert!(attempted_paths(Search::new().open()).is_empty());
+        assert!(message.contains("no candidates"), "{}", message);
+    }
+
+    #[test]
+    fn env_var_candidates_come_first() {
+        let key = "TANELORN_TEST_SEARCH_ENV_VAR";
+        let value =
+            std::env::join_paths(["libtanelorn-env-a.so", "", "libtanelorn-env-b.so"]).unwrap();
+        std::env::set_var(key, value);
+        let search = Search::new()
+            .env_var(key)
+            .env_var("TANELORN_TEST_SEARCH_UNSET")
+            .candidate("libtanelorn-missing.so");
+        std::env::remove_var(key);
+
+        let expected = [
+            Path::new("libtanelorn-env-a.so"),
+            Path::new("libtanelorn-env-b.so"),
+            Path::new("libtanelorn-missing.so"),
+        ];
+        assert_eq!(search.paths(), expected);
+        assert_eq!(attempted_paths(search.open()), expected);
+    }
+}
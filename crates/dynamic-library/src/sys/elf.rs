@@ -0,0 +1,200 @@
+//! Introspection of objects mapped by the glibc dynamic loader.
+
+use libc::{c_char, c_int, c_void, dl_iterate_phdr, dl_phdr_info, dlinfo, size_t, RTLD_DI_LINKMAP};
+
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+#[cfg(target_pointer_width = "64")]
+type Sym = libc::Elf64_Sym;
+#[cfg(target_pointer_width = "32")]
+type Sym = libc::Elf32_Sym;
+
+/// `ElfW(Dyn)`
+#[repr(C)]
+struct Dyn {
+    d_tag: isize,
+    d_val: usize,
+}
+
+/// The public part of glibc's `struct link_map`.
+#[repr(C)]
+struct LinkMap {
+    l_addr: usize,
+    l_name: *const c_char,
+    l_ld: *const Dyn,
+    l_next: *const LinkMap,
+    l_prev: *const LinkMap,
+}
+
+const DT_NULL: isize = 0;
+const DT_HASH: isize = 4;
+const DT_STRTAB: isize = 5;
+const DT_SYMTAB: isize = 6;
+const DT_GNU_HASH: isize = 0x6fff_fef5;
+
+const SHN_UNDEF: u16 = 0;
+const STB_GLOBAL: u8 = 1;
+const STB_WEAK: u8 = 2;
+const STB_GNU_UNIQUE: u8 = 10;
+const STT_SECTION: u8 = 3;
+const STT_FILE: u8 = 4;
+
+/// # Safety
+///
+/// `handle` must be a live handle returned by `dlopen`.
+pub unsafe fn path(handle: *mut c_void) -> Result<PathBuf, String> {
+    let map = link_map(handle)?;
+    name_to_path(map.l_name)
+}
+
+/// Names of the symbols defined and exported by the object behind `handle`.
+///
+/// # Safety
+///
+/// `handle` must be a live handle returned by `dlopen`.
+pub unsafe fn symbols(handle: *mut c_void) -> Result<Vec<String>, String> {
+    let map = link_map(handle)?;
+    if map.l_ld.is_null() {
+        return Err("object has no dynamic section".into());
+    }
+
+    let (mut symtab, mut strtab, mut hash, mut gnu_hash) = (0, 0, 0, 0);
+    let mut entry = map.l_ld;
+    while (*entry).d_tag != DT_NULL {
+        match (*entry).d_tag {
+            DT_SYMTAB => symtab = (*entry).d_val,
+            DT_STRTAB => strtab = (*entry).d_val,
+            DT_HASH => hash = (*entry).d_val,
+            DT_GNU_HASH => gnu_hash = (*entry).d_val,
+            _ => (),
+        }
+        entry = entry.add(1);
+    }
+
+    // glibc relocates these entries in place on most targets, but not on those
+    // with a read-only dynamic section.
+    let relocate = |ptr: usize| {
+        if ptr < map.l_addr {
+            ptr + map.l_addr
+        } else {
+            ptr
+        }
+    };
+    if symtab == 0 || strtab == 0 {
+        return Err("object has no dynamic symbol table".into());
+    }
+    let symtab = relocate(symtab) as *const Sym;
+    let strtab = relocate(strtab) as *const c_char;
+
+    let count = if gnu_hash != 0 {
+        gnu_hash_symbol_count(relocate(gnu_hash) as *const u32)
+    } else if hash != 0 {
+        // `nchain` equals the number of symbols.
+        *(relocate(hash) as *const u32).add(1) as usize
+    } else {
+        return Err("object has no symbol hash table".into());
+    };
+
+    let symbols = std::slice::from_raw_parts(symtab, count)
+        .iter()
+        .filter(|sym| {
+            let binding = sym.st_info >> 4;
+            let kind = sym.st_info & 0xf;
+
+            sym.st_name != 0
+                && sym.st_shndx != SHN_UNDEF
+                && matches!(binding, STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+                && !matches!(kind, STT_SECTION | STT_FILE)
+        })
+        .map(|sym| {
+            CStr::from_ptr(strtab.add(sym.st_name as usize))
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+
+    Ok(symbols)
+}
+
+/// Paths of every object currently mapped into the process, in load order.
+/// Objects without a file, like the vDSO, are skipped.
+pub fn loaded_modules() -> Result<Vec<PathBuf>, String> {
+    unsafe extern "C" fn callback(info: *mut dl_phdr_info, _: size_t, data: *mut c_void) -> c_int {
+        let modules = &mut *data.cast::<Vec<Result<PathBuf, String>>>();
+        let name = (*info).dlpi_name;
+        let bytes = if name.is_null() {
+            &[][..]
+        } else {
+            CStr::from_ptr(name).to_bytes()
+        };
+        // The main program has an empty name and files are named by their path,
+        // so anything else is a pseudo-object like `linux-vdso.so.1`.
+        if bytes.is_empty() || bytes.contains(&b'/') {
+            modules.push(name_to_path(name));
+        }
+        0
+    }
+
+    let mut modules = Vec::new();
+    // SAFETY: `callback` only runs during this call and `modules` outlives it.
+    unsafe { dl_iterate_phdr(Some(callback), (&mut modules as *mut Vec<_>).cast()) };
+
+    modules.into_iter().collect()
+}
+
+unsafe fn link_map<'a>(handle: *mut c_void) -> Result<&'a LinkMap, String> {
+    let mut map = std::ptr::null::<LinkMap>();
+    if dlinfo(
+        handle,
+        RTLD_DI_LINKMAP,
+        (&mut map as *mut *const LinkMap).cast(),
+    ) != 0
+    {
+        return Err(super::last_error());
+    }
+
+    map.as_ref().ok_or_else(|| "no link map".into())
+}
+
+/// The main program is reported with an empty name.
+unsafe fn name_to_path(name: *const c_char) -> Result<PathBuf, String> {
+    let name = if name.is_null() {
+        &[]
+    } else {
+        CStr::from_ptr(name).to_bytes()
+    };
+
+    if name.is_empty() {
+        std::env::current_exe().map_err(|e| e.to_string())
+    } else {
+        Ok(PathBuf::from(OsStr::from_bytes(name)))
+    }
+}
+
+/// `DT_GNU_HASH` does not store the symbol count, so it is found by walking to
+/// the end of the chain of the highest bucket.
+unsafe fn gnu_hash_symbol_count(table: *const u32) -> usize {
+    let bucket_count = *table as usize;
+    let symbol_offset = *table.add(1) as usize;
+    let bloom_size = *table.add(2) as usize;
+
+    let buckets = table.add(4).cast::<usize>().add(bloom_size).cast::<u32>();
+    let chains = buckets.add(bucket_count);
+
+    let last = (0..bucket_count)
+        .map(|i| *buckets.add(i) as usize)
+        .max()
+        .unwrap_or(0);
+    if last < symbol_offset {
+        return symbol_offset;
+    }
+
+    let mut index = last;
+    while *chains.add(index - symbol_offset) & 1 == 0 {
+        index += 1;
+    }
+
+    index + 1
+}
@@ -2,5 +2,10 @@ cfg_if::cfg_if! {
     if #[cfg(windows)] {
         mod windows;
         pub use self::windows::*;
+    } else if #[cfg(unix)] {
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        mod elf;
+        mod unix;
+        pub use self::unix::*;
     }
 }
@@ -0,0 +1,228 @@
+use libc::{
+    c_int, c_void, dlclose, dlerror, dlopen, dlsym, RTLD_GLOBAL, RTLD_LAZY, RTLD_LOCAL,
+    RTLD_NODELETE, RTLD_NOLOAD, RTLD_NOW,
+};
+
+use crate::{Error, Result};
+
+use std::ffi::{CStr, CString, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+
+pub struct DynamicLibrary(NonNull<c_void>);
+
+// SAFETY: Handles returned by `dlopen` are not bound to the thread that opened them
+unsafe impl Send for DynamicLibrary {}
+unsafe impl Sync for DynamicLibrary {}
+
+impl DynamicLibrary {
+    pub fn new<P: AsRef<Path>>(path: P, options: &LoadOptions) -> Result<Self> {
+        Self::open(path.as_ref(), options.mode())
+    }
+
+    pub fn this_process() -> Result<Self> {
+        // SAFETY: A null filename makes `dlopen` return a handle to the main program
+        let handle = unsafe { dlopen(std::ptr::null(), RTLD_LAZY | RTLD_LOCAL) };
+
+        NonNull::new(handle).map(Self).ok_or_else(|| Error::Load {
+            path: "<current process>".into(),
+            message: last_error(),
+        })
+    }
+
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open(path.as_ref(), RTLD_LAZY | RTLD_LOCAL | RTLD_NOLOAD)
+    }
+
+    fn open(path: &Path, mode: c_int) -> Result<Self> {
+        let os_path = <Path as AsRef<OsStr>>::as_ref(path);
+        let c_path = CString::new(os_path.as_bytes()).map_err(|_| Error::Load {
+            path: path.into(),
+            message: "path contains an interior nul byte".into(),
+        })?;
+
+        // SAFETY: `c_path` is valid null-terminated string
+        let handle = unsafe { dlopen(c_path.as_ptr(), mode) };
+
+        NonNull::new(handle).map(Self).ok_or_else(|| Error::Load {
+            path: path.into(),
+            message: if mode & RTLD_NOLOAD != 0 {
+                dlerror_message().unwrap_or_else(|| "not loaded in this process".into())
+            } else {
+                last_error()
+            },
+        })
+    }
+
+    pub fn get<T: AsRef<str>>(&self, proc: T) -> Result<*mut c_void> {
+        let name = proc.as_ref();
+        let proc = CString::new(name).map_err(|_| Error::Symbol {
+            name: name.into(),
+            message: "name contains an interior nul byte".into(),
+        })?;
+
+        // SAFETY: `self.0` is a live handle returned by `dlopen`. `dlerror` is
+        // cleared first so that a null symbol can be told apart from a failure.
+        let sym = unsafe {
+            dlerror();
+            dlsym(self.0.as_ptr(), proc.as_ptr())
+        };
+
+        if !sym.is_null() {
+            return Ok(sym);
+        }
+
+        // A symbol can legitimately resolve to null, e.g. an undefined weak
+        // symbol, in which case `dlerror` has nothing to report. Callers expect
+        // a non-null address, so it is still an error.
+        Err(Error::Symbol {
+            name: name.into(),
+            message: dlerror_message().unwrap_or_else(|| "symbol resolves to null".into()),
+        })
+    }
+
+    pub fn path(&self) -> Result<PathBuf> {
+        cfg_if::cfg_if! {
+            if #[cfg(all(target_os = "linux", target_env = "gnu"))] {
+                // SAFETY: `self.0` is a live handle returned by `dlopen`
+                unsafe { super::elf::path(self.0.as_ptr()) }.map_err(introspect_error)
+            } else {
+                Err(unsupported())
+            }
+        }
+    }
+
+    pub fn symbols(&self) -> Result<Vec<String>> {
+        cfg_if::cfg_if! {
+            if #[cfg(all(target_os = "linux", target_env = "gnu"))] {
+                // SAFETY: `self.0` is a live handle returned by `dlopen`
+                unsafe { super::elf::symbols(self.0.as_ptr()) }.map_err(introspect_error)
+            } else {
+                Err(unsupported())
+            }
+        }
+    }
+}
+
+impl Drop for DynamicLibrary {
+    fn drop(&mut self) {
+        // SAFETY: Since self is alive and has valid handle, we can safely close
+        unsafe { dlclose(self.0.as_ptr()) };
+    }
+}
+
+/// Flags passed to `dlopen`. Defaults to `RTLD_NOW | RTLD_LOCAL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadOptions {
+    resolve_now: bool,
+    global: bool,
+    no_delete: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            resolve_now: true,
+            global: false,
+            no_delete: false,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// `RTLD_NOW` if `true`, `RTLD_LAZY` otherwise. With eager resolution a
+    /// missing symbol makes loading fail instead of aborting on first call.
+    pub fn resolve_now(mut self, resolve_now: bool) -> Self {
+        self.resolve_now = resolve_now;
+        self
+    }
+
+    /// `RTLD_GLOBAL` if `true`, `RTLD_LOCAL` otherwise. Global symbols are
+    /// available to libraries loaded afterwards.
+    pub fn global(mut self, global: bool) -> Self {
+        self.global = global;
+        self
+    }
+
+    /// `RTLD_NODELETE`: keep the library mapped after it is closed.
+    pub fn no_delete(mut self, no_delete: bool) -> Self {
+        self.no_delete = no_delete;
+        self
+    }
+
+    fn mode(&self) -> c_int {
+        let mut mode = if self.resolve_now {
+            RTLD_NOW
+        } else {
+            RTLD_LAZY
+        };
+        mode |= if self.global { RTLD_GLOBAL } else { RTLD_LOCAL };
+        if self.no_delete {
+            mode |= RTLD_NODELETE;
+        }
+        mode
+    }
+}
+
+pub fn loaded_modules() -> Result<Vec<PathBuf>> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "linux", target_env = "gnu"))] {
+            super::elf::loaded_modules().map_err(introspect_error)
+        } else {
+            Err(unsupported())
+        }
+    }
+}
+
+fn introspect_error(message: String) -> Error {
+    Error::Introspect { message }
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+fn unsupported() -> Error {
+    introspect_error("not supported on this platform".into())
+}
+
+pub(super) fn last_error() -> String {
+    dlerror_message().unwrap_or_else(|| "unknown error".into())
+}
+
+fn dlerror_message() -> Option<String> {
+    // SAFETY: `dlerror` returns either null or a null-terminated string that
+    // stays valid until the next call, and we copy it out immediately.
+    unsafe {
+        let message = dlerror();
+        if message.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(message).to_string_lossy().into_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_options_map_to_dlopen_flags() {
+        let default = LoadOptions::default();
+
+        assert_eq!(default.mode(), RTLD_NOW | RTLD_LOCAL);
+        assert_eq!(default.resolve_now(false).mode(), RTLD_LAZY | RTLD_LOCAL);
+        assert_eq!(default.global(true).mode(), RTLD_NOW | RTLD_GLOBAL);
+        assert_eq!(
+            default.no_delete(true).mode(),
+            RTLD_NOW | RTLD_LOCAL | RTLD_NODELETE
+        );
+        assert_eq!(
+            default
+                .resolve_now(false)
+                .global(true)
+                .no_delete(true)
+                .mode(),
+            RTLD_LAZY | RTLD_GLOBAL | RTLD_NODELETE
+        );
+    }
+}
@@ -1,36 +1,98 @@
-use windows_sys::Win32::Foundation::{FARPROC, HINSTANCE};
-use windows_sys::Win32::System::LibraryLoader::{FreeLibrary, GetProcAddress, LoadLibraryW};
+use windows_sys::Win32::Foundation::{GetLastError, HINSTANCE};
+use windows_sys::Win32::System::Diagnostics::Debug::{
+    FormatMessageW, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS,
+};
+use windows_sys::Win32::System::LibraryLoader::{
+    FreeLibrary, GetModuleFileNameW, GetModuleHandleExW, GetProcAddress, LoadLibraryExW,
+    LOAD_LIBRARY_FLAGS, LOAD_LIBRARY_SEARCH_APPLICATION_DIR, LOAD_LIBRARY_SEARCH_DEFAULT_DIRS,
+    LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR, LOAD_LIBRARY_SEARCH_SYSTEM32, LOAD_LIBRARY_SEARCH_USER_DIRS,
+    LOAD_WITH_ALTERED_SEARCH_PATH,
+};
+use windows_sys::Win32::System::ProcessStatus::K32EnumProcessModules;
+use windows_sys::Win32::System::Threading::GetCurrentProcess;
 
-use std::ffi::{CString, OsStr};
-use std::os::windows::ffi::OsStrExt;
-use std::path::Path;
+use crate::{Error, Result};
+
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
 
 pub struct DynamicLibrary(HINSTANCE);
 
 impl DynamicLibrary {
-    pub fn new<P: AsRef<Path>>(path: P) -> Option<Self> {
+    pub fn new<P: AsRef<Path>>(path: P, options: &LoadOptions) -> Result<Self> {
         let path = path.as_ref();
-        let os_path = <Path as AsRef<OsStr>>::as_ref(path);
-        let wide_path = os_path
-            .encode_wide()
-            .chain(std::iter::once(0))
-            .collect::<Vec<u16>>();
+        let wide_path = to_wide(path);
 
         // SAFETY: `wide_path` is valid null-terminated UTF-16
-        let module = unsafe { LoadLibraryW(wide_path.as_ptr()) };
+        let module = unsafe { LoadLibraryExW(wide_path.as_ptr(), 0, options.flags) };
 
         if module != 0 {
             // SAFETY: Function succeeded
-            Some(Self(module))
+            Ok(Self(module))
         } else {
-            None
+            Err(Error::Load {
+                path: path.into(),
+                message: last_error(),
+            })
         }
     }
 
-    pub fn get<T: AsRef<str>>(&self, proc: T) -> FARPROC {
-        let proc = CString::new(proc.as_ref()).unwrap();
+    pub fn this_process() -> Result<Self> {
+        let mut module = 0;
+
+        // SAFETY: A null module name returns the handle of the executable. The
+        // reference count is incremented, so `FreeLibrary` on drop is balanced.
+        if unsafe { GetModuleHandleExW(0, std::ptr::null(), &mut module) } != 0 {
+            Ok(Self(module))
+        } else {
+            Err(Error::Load {
+                path: "<current process>".into(),
+                message: last_error(),
+            })
+        }
+    }
+
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let wide_path = to_wide(path);
+        let mut module = 0;
+
+        // SAFETY: `wide_path` is valid null-terminated UTF-16. The reference
+        // count is incremented, so `FreeLibrary` on drop is balanced.
+        if unsafe { GetModuleHandleExW(0, wide_path.as_ptr(), &mut module) } != 0 {
+            Ok(Self(module))
+        } else {
+            Err(Error::Load {
+                path: path.into(),
+                message: last_error(),
+            })
+        }
+    }
+
+    pub fn get<T: AsRef<str>>(&self, proc: T) -> Result<unsafe extern "system" fn() -> isize> {
+        let name = proc.as_ref();
+        let proc = CString::new(name).map_err(|_| Error::Symbol {
+            name: name.into(),
+            message: "name contains an interior nul byte".into(),
+        })?;
+
+        match unsafe { GetProcAddress(self.0, proc.as_ptr().cast()) } {
+            Some(sym) => Ok(sym),
+            None => Err(Error::Symbol {
+                name: name.into(),
+                message: last_error(),
+            }),
+        }
+    }
+
+    pub fn path(&self) -> Result<PathBuf> {
+        module_path(self.0)
+    }
 
-        unsafe { GetProcAddress(self.0, proc.as_ptr().cast()) }
+    pub fn symbols(&self) -> Result<Vec<String>> {
+        // SAFETY: A module handle is the base address of its mapped PE image.
+        unsafe { export_names(self.0 as *const u8) }
     }
 }
 
@@ -40,3 +102,182 @@ impl Drop for DynamicLibrary {
         unsafe { FreeLibrary(self.0) };
     }
 }
+
+/// Flags passed to `LoadLibraryExW`. Defaults to none, which behaves like
+/// `LoadLibraryW`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    flags: LOAD_LIBRARY_FLAGS,
+}
+
+impl LoadOptions {
+    /// `LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR`: search the directory of the DLL
+    /// for its dependencies. Requires an absolute path.
+    pub fn search_dll_load_dir(self, enabled: bool) -> Self {
+        self.flag(LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR, enabled)
+    }
+
+    /// `LOAD_LIBRARY_SEARCH_APPLICATION_DIR`
+    pub fn search_application_dir(self, enabled: bool) -> Self {
+        self.flag(LOAD_LIBRARY_SEARCH_APPLICATION_DIR, enabled)
+    }
+
+    /// `LOAD_LIBRARY_SEARCH_USER_DIRS`: directories added with `AddDllDirectory`.
+    pub fn search_user_dirs(self, enabled: bool) -> Self {
+        self.flag(LOAD_LIBRARY_SEARCH_USER_DIRS, enabled)
+    }
+
+    /// `LOAD_LIBRARY_SEARCH_SYSTEM32`
+    pub fn search_system32(self, enabled: bool) -> Self {
+        self.flag(LOAD_LIBRARY_SEARCH_SYSTEM32, enabled)
+    }
+
+    /// `LOAD_LIBRARY_SEARCH_DEFAULT_DIRS`: application directory, system32 and
+    /// user directories.
+    pub fn search_default_dirs(self, enabled: bool) -> Self {
+        self.flag(LOAD_LIBRARY_SEARCH_DEFAULT_DIRS, enabled)
+    }
+
+    /// `LOAD_WITH_ALTERED_SEARCH_PATH`: resolve dependencies relative to the
+    /// DLL's own directory. Cannot be combined with the `search_*` flags.
+    pub fn altered_search_path(self, enabled: bool) -> Self {
+        self.flag(LOAD_WITH_ALTERED_SEARCH_PATH, enabled)
+    }
+
+    fn flag(mut self, flag: LOAD_LIBRARY_FLAGS, enabled: bool) -> Self {
+        if enabled {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        self
+    }
+}
+
+pub fn loaded_modules() -> Result<Vec<PathBuf>> {
+    let mut modules = vec![0; 256];
+    loop {
+        let size = std::mem::size_of_val(modules.as_slice()) as u32;
+        let mut needed = 0;
+
+        // SAFETY: `modules` is writable for `size` bytes. The pseudo handle of
+        // the current process does not need to be closed.
+        let ok = unsafe {
+            K32EnumProcessModules(GetCurrentProcess(), modules.as_mut_ptr(), size, &mut needed)
+        };
+        if ok == 0 {
+            return Err(Error::Introspect {
+                message: last_error(),
+            });
+        }
+
+        let count = needed as usize / std::mem::size_of::<HINSTANCE>();
+        if needed <= size {
+            modules.truncate(count);
+            break;
+        }
+        modules.resize(count, 0);
+    }
+
+    modules.into_iter().map(module_path).collect()
+}
+
+fn module_path(module: HINSTANCE) -> Result<PathBuf> {
+    let mut buffer = vec![0u16; 260];
+    loop {
+        // SAFETY: `buffer` is writable for `buffer.len()` UTF-16 code units
+        let len = unsafe { GetModuleFileNameW(module, buffer.as_mut_ptr(), buffer.len() as u32) }
+            as usize;
+
+        if len == 0 {
+            return Err(Error::Introspect {
+                message: last_error(),
+            });
+        } else if len < buffer.len() {
+            return Ok(OsString::from_wide(&buffer[..len]).into());
+        }
+
+        // The path was truncated.
+        buffer.resize(buffer.len() * 2, 0);
+    }
+}
+
+/// Reads the names in the export directory of the PE image at `base`.
+///
+/// # Safety
+///
+/// `base` must point to a PE image mapped by the loader.
+unsafe fn export_names(base: *const u8) -> Result<Vec<String>> {
+    const IMAGE_NT_OPTIONAL_HDR32_MAGIC: u16 = 0x10b;
+    const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20b;
+
+    let read_u16 = |offset: usize| base.add(offset).cast::<u16>().read_unaligned();
+    let read_u32 = |offset: usize| base.add(offset).cast::<u32>().read_unaligned();
+
+    // `IMAGE_DOS_HEADER::e_lfanew`
+    let nt_headers = read_u32(0x3c) as usize;
+    // Skip the signature and `IMAGE_FILE_HEADER`.
+    let optional_header = nt_headers + 4 + 20;
+    let data_directories = match read_u16(optional_header) {
+        IMAGE_NT_OPTIONAL_HDR32_MAGIC => optional_header + 96,
+        IMAGE_NT_OPTIONAL_HDR64_MAGIC => optional_header + 112,
+        magic => {
+            return Err(Error::Introspect {
+                message: format!("unknown optional header magic {:#x}", magic),
+            })
+        }
+    };
+
+    // `IMAGE_DIRECTORY_ENTRY_EXPORT` is the first data directory.
+    let export_directory = read_u32(data_directories) as usize;
+    if export_directory == 0 {
+        return Ok(Vec::new());
+    }
+
+    // `IMAGE_EXPORT_DIRECTORY::NumberOfNames` and `AddressOfNames`
+    let name_count = read_u32(export_directory + 24) as usize;
+    let names = read_u32(export_directory + 32) as usize;
+
+    Ok((0..name_count)
+        .map(|i| {
+            let name = read_u32(names + i * 4) as usize;
+            CStr::from_ptr(base.add(name).cast())
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect())
+}
+
+fn to_wide(path: &Path) -> Vec<u16> {
+    <Path as AsRef<OsStr>>::as_ref(path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+fn last_error() -> String {
+    let mut buffer = [0u16; 512];
+
+    // SAFETY: `buffer` is writable for `buffer.len()` UTF-16 code units and no
+    // insert arguments are used.
+    let (code, len) = unsafe {
+        let code = GetLastError();
+        let len = FormatMessageW(
+            FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+            std::ptr::null(),
+            code,
+            0,
+            buffer.as_mut_ptr(),
+            buffer.len() as u32,
+            std::ptr::null(),
+        );
+        (code, len as usize)
+    };
+
+    if len == 0 {
+        format!("os error {}", code)
+    } else {
+        let message = String::from_utf16_lossy(&buffer[..len]);
+        format!("{} (os error {})", message.trim_end(), code)
+    }
+}
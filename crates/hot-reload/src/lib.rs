@@ -0,0 +1,279 @@
+//! Hot reloading of game modules built as `cdylib`s.
+//!
+//! A module is a [plugin](dynamic_library::plugin) that exports four entry
+//! points with the C ABI:
+//!
+//! ```ignore
+//! dynamic_library::export_plugin! {
+//!     name: "game",
+//!     capabilities: [],
+//! }
+//!
+//! #[no_mangle]
+//! pub extern "C" fn tanelorn_module_init() -> *mut c_void;
+//! #[no_mangle]
+//! pub extern "C" fn tanelorn_module_update(state: *mut c_void);
+//! #[no_mangle]
+//! pub extern "C" fn tanelorn_module_reload(state: *mut c_void) -> *mut c_void;
+//! #[no_mangle]
+//! pub extern "C" fn tanelorn_module_shutdown(state: *mut c_void);
+//! ```
+//!
+//! `init` is called once when the module is first loaded and returns the state
+//! owned by the module. After a rebuild, the state is handed to `reload` of the
+//! new build, which may migrate it and returns the state to use from then on.
+//! `shutdown` is called with the last state when the [`HotModule`] is dropped.
+
+use dynamic_library::{DynamicLibrary, Plugin};
+
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const INIT_SYMBOL: &str = "tanelorn_module_init";
+pub const UPDATE_SYMBOL: &str = "tanelorn_module_update";
+pub const RELOAD_SYMBOL: &str = "tanelorn_module_reload";
+pub const SHUTDOWN_SYMBOL: &str = "tanelorn_module_shutdown";
+
+pub type InitFn = unsafe extern "C" fn() -> *mut c_void;
+pub type UpdateFn = unsafe extern "C" fn(*mut c_void);
+pub type ReloadFn = unsafe extern "C" fn(*mut c_void) -> *mut c_void;
+pub type ShutdownFn = unsafe extern "C" fn(*mut c_void);
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Library(dynamic_library::Error),
+}
+
+impl From<dynamic_library::Error> for Error {
+    fn from(e: dynamic_library::Error) -> Self {
+        Self::Library(e)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "`{}`: {}", path.display(), source),
+            Self::Library(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Library(e) => Some(e),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A game module that is reloaded whenever its library changes on disk.
+///
+/// The library is never loaded from its build location. Each version is copied
+/// to its own path in the temporary directory first, so the build can overwrite
+/// the original while the previous version is still loaded.
+pub struct HotModule {
+    source: PathBuf,
+    modified: SystemTime,
+    version: u64,
+    state: *mut c_void,
+    loaded: LoadedModule,
+}
+
+impl HotModule {
+    /// Loads the module at `path` and calls its `init` entry point.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let source = path.as_ref().to_path_buf();
+        let modified = modified(&source)?;
+        let loaded = LoadedModule::load(&source, 0)?;
+
+        // SAFETY: `init` was resolved with the signature documented by this crate.
+        let state = unsafe { (loaded.init)() };
+        log::info!("Loaded module `{}`.", source.display());
+
+        Ok(Self {
+            source,
+            modified,
+            version: 0,
+            state,
+            loaded,
+        })
+    }
+
+    /// Calls the `update` entry point of the current version.
+    pub fn update(&mut self) {
+        // SAFETY: `state` was returned by `init` or `reload` of this module.
+        unsafe { (self.loaded.update)(self.state) }
+    }
+
+    /// Reloads the module if the library on disk changed since it was last
+    /// loaded. Returns whether a reload happened.
+    ///
+    /// If the new build cannot be loaded, e.g. because it is still being
+    /// written, the current version stays active and the library is tried
+    /// again once it changes another time.
+    pub fn reload_if_changed(&mut self) -> Result<bool> {
+        let modified = modified(&self.source)?;
+        if modified == self.modified {
+            return Ok(false);
+        }
+
+        self.modified = modified;
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// Loads the library on disk as a new version and hands the current state
+    /// to its `reload` entry point.
+    pub fn reload(&mut self) -> Result<()> {
+        let version = self.version + 1;
+        let loaded = LoadedModule::load(&self.source, version)?;
+
+        // SAFETY: `reload` was resolved with the signature documented by this
+        // crate, and the previous version is still loaded while it runs.
+        self.state = unsafe { (loaded.reload)(self.state) };
+        self.loaded = loaded;
+        self.version = version;
+        log::info!(
+            "Reloaded module `{}` (version {}).",
+            self.source.display(),
+            version
+        );
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.source
+    }
+
+    /// Name from the plugin descriptor of the current version.
+    pub fn name(&self) -> &str {
+        self.loaded.plugin().name()
+    }
+
+    /// Number of reloads since the module was first loaded.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl Drop for HotModule {
+    fn drop(&mut self) {
+        // SAFETY: `state` was returned by `init` or `reload` of this module.
+        unsafe { (self.loaded.shutdown)(self.state) };
+        log::info!("Unloaded module `{}`.", self.source.display());
+    }
+}
+
+/// One version of a module, loaded from its own copy of the library.
+struct LoadedModule {
+    init: InitFn,
+    update: UpdateFn,
+    reload: ReloadFn,
+    shutdown: ShutdownFn,
+    // Declared after the entry points, which must not be called once it drops.
+    plugin: Option<Plugin>,
+    shadow: PathBuf,
+}
+
+impl LoadedModule {
+    fn load(source: &Path, version: u64) -> Result<Self> {
+        let shadow = shadow_path(source, version);
+        if let Some(parent) = shadow.parent() {
+            std::fs::create_dir_all(parent).map_err(|source| Error::Io {
+                path: parent.into(),
+                source,
+            })?;
+        }
+        std::fs::copy(source, &shadow).map_err(|e| Error::Io {
+            path: source.into(),
+            source: e,
+        })?;
+
+        match Self::open(&shadow, source) {
+            Ok((plugin, init, update, reload, shutdown)) => Ok(Self {
+                init,
+                update,
+                reload,
+                shutdown,
+                plugin: Some(plugin),
+                shadow,
+            }),
+            Err(e) => {
+                let _ = std::fs::remove_file(&shadow);
+                Err(e)
+            }
+        }
+    }
+
+    fn plugin(&self) -> &Plugin {
+        // Only taken in `drop`.
+        self.plugin.as_ref().unwrap()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn open(
+        path: &Path,
+        source: &Path,
+    ) -> Result<(Plugin, InitFn, UpdateFn, ReloadFn, ShutdownFn)> {
+        let plugin = Plugin::new(DynamicLibrary::new(path)?, source)?;
+        let lib = plugin.library();
+
+        // SAFETY: The entry points are documented to have these signatures. The
+        // pointers are copied out of their symbols but only called while `lib`
+        // is kept alive next to them in `LoadedModule`.
+        unsafe {
+            let init = *lib.get_typed::<InitFn>(INIT_SYMBOL)?;
+            let update = *lib.get_typed::<UpdateFn>(UPDATE_SYMBOL)?;
+            let reload = *lib.get_typed::<ReloadFn>(RELOAD_SYMBOL)?;
+            let shutdown = *lib.get_typed::<ShutdownFn>(SHUTDOWN_SYMBOL)?;
+
+            Ok((plugin, init, update, reload, shutdown))
+        }
+    }
+}
+
+impl Drop for LoadedModule {
+    fn drop(&mut self) {
+        // The library has to be unloaded before its file can be removed on Windows.
+        drop(self.plugin.take());
+        if let Err(e) = std::fs::remove_file(&self.shadow) {
+            log::warn!("Could not remove `{}`: {}", self.shadow.display(), e);
+        }
+    }
+}
+
+/// `<temp>/tanelorn-hot-reload/<stem>-<pid>-<version>.<ext>`
+fn shadow_path(source: &Path, version: u64) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy())
+        .unwrap_or_default();
+    let mut file_name = format!("{}-{}-{}", stem, std::process::id(), version);
+    if let Some(ext) = source.extension() {
+        file_name.push('.');
+        file_name.push_str(&ext.to_string_lossy());
+    }
+
+    std::env::temp_dir()
+        .join("tanelorn-hot-reload")
+        .join(file_name)
+}
+
+fn modified(path: &Path) -> Result<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|source| Error::Io {
+            path: path.into(),
+            source,
+        })
+}
@@ -0,0 +1,44 @@
+//! Game module rebuilt by the hot reload tests. Every update adds the step it
+//! was built with to a counter kept in its state, and writes the counter to
+//! the file named by `TANELORN_FIXTURE_OUT`.
+
+use std::ffi::c_void;
+
+dynamic_library::export_plugin! {
+    name: "fixture",
+    capabilities: [],
+}
+
+/// Set through `TANELORN_FIXTURE_STEP` when building, 1 by default.
+const STEP: Option<&str> = option_env!("TANELORN_FIXTURE_STEP");
+
+#[no_mangle]
+pub extern "C" fn tanelorn_module_init() -> *mut c_void {
+    Box::into_raw(Box::new(0u64)).cast()
+}
+
+/// # Safety
+///
+/// `state` must have been returned by `init` or `reload`.
+#[no_mangle]
+pub unsafe extern "C" fn tanelorn_module_update(state: *mut c_void) {
+    let counter = &mut *state.cast::<u64>();
+    *counter += STEP.map_or(1, |s| s.parse().expect("Step is not a number"));
+
+    if let Some(path) = std::env::var_os("TANELORN_FIXTURE_OUT") {
+        std::fs::write(path, counter.to_string()).expect("Could not write counter");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn tanelorn_module_reload(state: *mut c_void) -> *mut c_void {
+    state
+}
+
+/// # Safety
+///
+/// `state` must have been returned by `init` or `reload`.
+#[no_mangle]
+pub unsafe extern "C" fn tanelorn_module_shutdown(state: *mut c_void) {
+    drop(Box::from_raw(state.cast::<u64>()));
+}
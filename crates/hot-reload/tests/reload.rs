@@ -0,0 +1,51 @@
+use hot_reload::HotModule;
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Builds the fixture module with `step` into `target_dir` and returns the
+/// path of the library.
+fn build_fixture(target_dir: &Path, step: u64) -> PathBuf {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixture/Cargo.toml");
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(manifest)
+        .arg("--target-dir")
+        .arg(target_dir)
+        .env("TANELORN_FIXTURE_STEP", step.to_string())
+        .status()
+        .expect("Could not run cargo");
+    assert!(status.success(), "Could not build the fixture module");
+
+    target_dir
+        .join("debug")
+        .join(format!("{}hot_reload_fixture{}", DLL_PREFIX, DLL_SUFFIX))
+}
+
+fn read_counter(path: &Path) -> u64 {
+    std::fs::read_to_string(path).unwrap().parse().unwrap()
+}
+
+#[test]
+fn reload_keeps_state_and_runs_new_code() {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("hot-reload-fixture");
+    let out = target_dir.join("counter");
+    std::env::set_var("TANELORN_FIXTURE_OUT", &out);
+
+    let path = build_fixture(&target_dir, 1);
+    let mut module = HotModule::load(&path).unwrap();
+    assert_eq!(module.name(), "fixture");
+    module.update();
+    module.update();
+    assert_eq!(read_counter(&out), 2);
+    assert!(!module.reload_if_changed().unwrap());
+
+    build_fixture(&target_dir, 10);
+    assert!(module.reload_if_changed().unwrap());
+    assert_eq!(module.version(), 1);
+    module.update();
+    assert_eq!(read_counter(&out), 12);
+}
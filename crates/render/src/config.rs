@@ -0,0 +1,216 @@
+use crate::vulkan::{DeviceFeatures, InstanceBuilder, Validation};
+
+use crate::{GpuSelection, PresentMode};
+use ash::vk;
+
+/// Overrides [`RendererConfig::validation`]: `0`, `false` or `off` disable
+/// validation, `panic` also panics on validation errors, anything else enables it.
+pub const VALIDATION_ENV_VAR: &str = "TANELORN_VALIDATION";
+
+/// Options for creating a [`Renderer`](crate::Renderer).
+#[derive(Debug, Clone)]
+pub struct RendererConfig {
+    /// Everything about the instance except validation, which is resolved when
+    /// the renderer is created.
+    instance: InstanceBuilder,
+    pub(crate) present_modes: Vec<PresentMode>,
+    pub(crate) gpu: GpuSelection,
+    validation: bool,
+    panic_on_validation_error: bool,
+    pub(crate) required_device_extensions: Vec<String>,
+    pub(crate) optional_device_extensions: Vec<String>,
+    pub(crate) required_device_features: DeviceFeatures,
+    pub(crate) optional_device_features: DeviceFeatures,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            instance: InstanceBuilder::new(),
+            present_modes: vec![PresentMode::Fifo],
+            gpu: GpuSelection::Auto,
+            validation: cfg!(debug_assertions),
+            panic_on_validation_error: false,
+            required_device_extensions: Vec::new(),
+            optional_device_extensions: Vec::new(),
+            required_device_features: DeviceFeatures::default(),
+            optional_device_features: DeviceFeatures::default(),
+        }
+    }
+}
+
+impl RendererConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reported to the driver, which may use it to apply workarounds.
+    pub fn app_name(mut self, name: impl Into<String>) -> Self {
+        self.instance = self.instance.app_name(name);
+        self
+    }
+
+    pub fn app_version(mut self, major: u32, minor: u32, patch: u32) -> Self {
+        self.instance = self
+            .instance
+            .app_version(vk::make_api_version(0, major, minor, patch));
+        self
+    }
+
+    /// Creating the renderer fails if the Vulkan loader does not support at
+    /// least this version. Defaults to 1.0.
+    pub fn min_api_version(mut self, major: u32, minor: u32) -> Self {
+        self.instance = self
+            .instance
+            .min_api_version(vk::make_api_version(0, major, minor, 0));
+        self
+    }
+
+    /// The highest Vulkan version to use, even if the loader supports a newer
+    /// one. Defaults to 1.3.
+    pub fn max_api_version(mut self, major: u32, minor: u32) -> Self {
+        self.instance = self
+            .instance
+            .max_api_version(vk::make_api_version(0, major, minor, 0));
+        self
+    }
+
+    /// Also considers GPUs that do not fully conform to Vulkan, such as
+    /// MoltenVK on macOS.
+    pub fn portability_enumeration(mut self, enabled: bool) -> Self {
+        self.instance = self.instance.portability_enumeration(enabled);
+        self
+    }
+
+    /// Instance extensions that must be supported, in addition to those the
+    /// renderer needs itself.
+    pub fn required_instance_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.instance = self.instance.required_extensions(extensions);
+        self
+    }
+
+    /// Instance extensions that are enabled if they are supported.
+    pub fn optional_instance_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.instance = self.instance.optional_extensions(extensions);
+        self
+    }
+
+    /// Instance layers that must be installed.
+    pub fn required_layers<I, S>(mut self, layers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.instance = self.instance.required_layers(layers);
+        self
+    }
+
+    /// Instance layers that are enabled if they are installed.
+    pub fn optional_layers<I, S>(mut self, layers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.instance = self.instance.optional_layers(layers);
+        self
+    }
+
+    /// Present modes in order of preference. Modes the surface does not support
+    /// are skipped, and FIFO is used if none of them is supported.
+    pub fn present_modes(mut self, present_modes: impl IntoIterator<Item = PresentMode>) -> Self {
+        self.present_modes = present_modes.into_iter().collect();
+        self
+    }
+
+    /// The physical device to use. [`GPU_ENV_VAR`](crate::GPU_ENV_VAR) takes
+    /// precedence.
+    pub fn gpu(mut self, gpu: GpuSelection) -> Self {
+        self.gpu = gpu;
+        self
+    }
+
+    /// Enables `VK_LAYER_KHRONOS_validation` and forwards its messages to `log`.
+    /// On by default in debug builds.
+    pub fn validation(mut self, validation: bool) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Panics when a validation error is reported, after the call that caused
+    /// it returns. Implies [`validation`](Self::validation).
+    pub fn panic_on_validation_error(mut self, panic: bool) -> Self {
+        self.panic_on_validation_error = panic;
+        self
+    }
+
+    /// Device extensions that the physical device must support, in addition to
+    /// those the renderer needs itself.
+    pub fn required_device_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_device_extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Device extensions that are enabled if the physical device supports them.
+    /// See [`Renderer::is_device_extension_enabled`](crate::Renderer::is_device_extension_enabled).
+    pub fn optional_device_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.optional_device_extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Device features that the physical device must support.
+    pub fn required_device_features(mut self, features: DeviceFeatures) -> Self {
+        self.required_device_features = self.required_device_features.union(&features);
+        self
+    }
+
+    /// Device features that are enabled if the physical device supports them.
+    /// See [`Renderer::enabled_device_features`](crate::Renderer::enabled_device_features).
+    pub fn optional_device_features(mut self, features: DeviceFeatures) -> Self {
+        self.optional_device_features = self.optional_device_features.union(&features);
+        self
+    }
+
+    /// `renderer_extensions` followed by the required device extensions.
+    pub(crate) fn device_extensions<'a>(&'a self, renderer_extensions: &[&'a str]) -> Vec<&'a str> {
+        renderer_extensions
+            .iter()
+            .copied()
+            .chain(self.required_device_extensions.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// The validation setting after applying [`VALIDATION_ENV_VAR`].
+    pub(crate) fn resolve_validation(&self) -> Validation {
+        match std::env::var(VALIDATION_ENV_VAR).ok().as_deref() {
+            Some("0" | "false" | "off") => Validation::Disabled,
+            Some("panic") => Validation::PanicOnError,
+            Some(_) => Validation::Enabled,
+            None if self.panic_on_validation_error => Validation::PanicOnError,
+            None if self.validation => Validation::Enabled,
+            None => Validation::Disabled,
+        }
+    }
+
+    /// An instance builder with the instance options and validation setting.
+    pub(crate) fn instance_builder(&self) -> InstanceBuilder {
+        self.instance.clone().validation(self.resolve_validation())
+    }
+}
@@ -0,0 +1,280 @@
+use ash::vk as raw;
+
+use crate::vulkan as vk;
+use crate::{Error, Result};
+
+/// Overrides [`RendererConfig::gpu`](crate::RendererConfig::gpu): a number
+/// selects a physical device by index, anything else by name.
+pub const GPU_ENV_VAR: &str = "TANELORN_GPU";
+
+/// Which physical device the renderer uses.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GpuSelection {
+    /// The suitable device with the highest score.
+    #[default]
+    Auto,
+    /// The suitable device with the highest score among those whose name
+    /// contains this, ignoring case.
+    Name(String),
+    /// The device at this position in enumeration order.
+    Index(usize),
+}
+
+impl GpuSelection {
+    fn parse(s: &str) -> Self {
+        match s.parse() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(s.to_owned()),
+        }
+    }
+
+    /// `selection` after applying [`GPU_ENV_VAR`].
+    pub(crate) fn resolve(selection: &Self) -> Self {
+        match std::env::var(GPU_ENV_VAR) {
+            Ok(s) if !s.is_empty() => Self::parse(&s),
+            _ => selection.clone(),
+        }
+    }
+
+    /// Whether the device at `index` in enumeration order called `device_name`
+    /// is selected.
+    fn matches(&self, index: usize, device_name: &str) -> bool {
+        match self {
+            Self::Auto => true,
+            Self::Name(name) => device_name.to_lowercase().contains(&name.to_lowercase()),
+            Self::Index(i) => *i == index,
+        }
+    }
+}
+
+impl std::fmt::Display for GpuSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Name(name) => write!(f, "{}", name),
+            Self::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// What a physical device needs to be usable by the renderer.
+pub(crate) struct Requirements<'a> {
+    pub(crate) extensions: &'a [&'a str],
+    pub(crate) features: vk::DeviceFeatures,
+    /// The surface that has to be presented to, if any.
+    pub(crate) surface: Option<&'a vk::SurfaceKhr>,
+}
+
+/// Scores every physical device and picks the best suitable one, or the one
+/// `selection` names. Returns it with the queue families to use.
+pub(crate) fn select_physical_device(
+    instance: &vk::Instance,
+    selection: &GpuSelection,
+    requirements: Requirements<'_>,
+) -> Result<(vk::QueueFamilies, vk::PhysicalDevice)> {
+    let physical_devices = instance.enumerate_physical_devices()?.collect::<Vec<_>>();
+    if physical_devices.is_empty() {
+        return Err(Error::NoPhysicalDevice);
+    }
+
+    let mut suitable = Vec::new();
+    let mut queue_families = vec![None; physical_devices.len()];
+    let mut first_error = None;
+    let mut matched = false;
+
+    for (index, physical_device) in physical_devices.iter().enumerate() {
+        let candidate = Candidate::new(index, physical_device);
+        let status = if !selection.matches(index, candidate.name) {
+            String::from("not selected")
+        } else {
+            matched = true;
+            match evaluate(physical_device, &requirements) {
+                Ok(families) => {
+                    queue_families[index] = Some(families);
+                    suitable.push(candidate);
+                    String::from("suitable")
+                }
+                Err(e) => {
+                    let status = format!("unsuitable: {}", e);
+                    first_error.get_or_insert(e);
+                    status
+                }
+            }
+        };
+        log::info!(
+            "GPU {}: {} ({:?}), score {}, {}",
+            index,
+            candidate.name,
+            candidate.device_type,
+            candidate.score(),
+            status
+        );
+    }
+
+    match choose(selection, suitable).map(|candidate| candidate.index) {
+        Some(index) => {
+            let queue_families =
+                queue_families[index].expect("Suitable devices have queue families");
+            let physical_device = physical_devices
+                .into_iter()
+                .nth(index)
+                .expect("Index of an enumerated device");
+            Ok((queue_families, physical_device))
+        }
+        None if !matched => Err(Error::DeviceNotFound(selection.to_string())),
+        None => Err(first_error.unwrap_or(Error::MissingQueueFamily("graphics"))),
+    }
+}
+
+/// The queue families to use if the device is suitable.
+fn evaluate(
+    physical_device: &vk::PhysicalDevice,
+    requirements: &Requirements<'_>,
+) -> Result<vk::QueueFamilies> {
+    physical_device.check_support(requirements.extensions, &requirements.features)?;
+    physical_device.queue_families(requirements.surface)
+}
+
+/// The properties of a physical device that it is selected and scored by.
+#[derive(Debug, Clone, Copy)]
+struct Candidate<'a> {
+    index: usize,
+    name: &'a str,
+    device_type: vk::PhysicalDeviceType,
+    api_version: u32,
+    device_local_memory_size: u64,
+}
+
+impl<'a> Candidate<'a> {
+    fn new(index: usize, physical_device: &'a vk::PhysicalDevice) -> Self {
+        Self {
+            index,
+            name: physical_device.device_name(),
+            device_type: physical_device.device_type(),
+            api_version: physical_device.api_version(),
+            device_local_memory_size: physical_device.device_local_memory_size(),
+        }
+    }
+
+    /// Prefers discrete GPUs, then newer API versions, then more device memory.
+    fn score(&self) -> u32 {
+        let device_type = match self.device_type {
+            vk::PhysicalDeviceType::DiscreteGpu => 1000,
+            vk::PhysicalDeviceType::IntegratedGpu => 500,
+            vk::PhysicalDeviceType::VirtualGpu => 200,
+            vk::PhysicalDeviceType::Cpu => 100,
+            vk::PhysicalDeviceType::Other => 0,
+        };
+        let api_version = raw::api_version_minor(self.api_version) * 10;
+        let memory_gib = (self.device_local_memory_size >> 30).min(64) as u32;
+
+        device_type + api_version + memory_gib
+    }
+}
+
+/// The highest scoring of the suitable `candidates` that `selection` matches.
+/// Ties go to the one enumerated first.
+fn choose<'a>(
+    selection: &GpuSelection,
+    candidates: impl IntoIterator<Item = Candidate<'a>>,
+) -> Option<Candidate<'a>> {
+    let mut best: Option<Candidate<'a>> = None;
+    for candidate in candidates {
+        if selection.matches(candidate.index, candidate.name)
+            && best.is_none_or(|best| candidate.score() > best.score())
+        {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_index_or_name() {
+        assert_eq!(GpuSelection::parse("1"), GpuSelection::Index(1));
+        assert_eq!(
+            GpuSelection::parse("RTX 4090"),
+            GpuSelection::Name("RTX 4090".into())
+        );
+        assert_eq!(GpuSelection::parse("-1"), GpuSelection::Name("-1".into()));
+    }
+
+    fn candidate(
+        index: usize,
+        name: &'static str,
+        device_type: vk::PhysicalDeviceType,
+    ) -> Candidate<'static> {
+        Candidate {
+            index,
+            name,
+            device_type,
+            api_version: raw::API_VERSION_1_3,
+            device_local_memory_size: 8 << 30,
+        }
+    }
+
+    #[test]
+    fn prefers_discrete_then_newer_then_more_memory() {
+        let discrete = candidate(0, "discrete", vk::PhysicalDeviceType::DiscreteGpu);
+        let integrated = candidate(1, "integrated", vk::PhysicalDeviceType::IntegratedGpu);
+        let cpu = candidate(2, "cpu", vk::PhysicalDeviceType::Cpu);
+        assert!(discrete.score() > integrated.score());
+        assert!(integrated.score() > cpu.score());
+
+        let older = Candidate {
+            api_version: raw::API_VERSION_1_1,
+            ..integrated
+        };
+        let smaller = Candidate {
+            device_local_memory_size: 1 << 30,
+            ..integrated
+        };
+        assert!(integrated.score() > older.score());
+        assert!(integrated.score() > smaller.score());
+        // Memory never outweighs the device type.
+        let huge = Candidate {
+            device_local_memory_size: 1 << 50,
+            ..integrated
+        };
+        assert!(discrete.score() > huge.score());
+    }
+
+    #[test]
+    fn chooses_best_selected_candidate() {
+        let candidates = [
+            candidate(
+                0,
+                "Intel UHD Graphics",
+                vk::PhysicalDeviceType::IntegratedGpu,
+            ),
+            candidate(
+                1,
+                "NVIDIA GeForce RTX 4090",
+                vk::PhysicalDeviceType::DiscreteGpu,
+            ),
+            candidate(2, "llvmpipe", vk::PhysicalDeviceType::Cpu),
+        ];
+        let chosen = |selection: GpuSelection| choose(&selection, candidates).map(|c| c.index);
+
+        assert_eq!(chosen(GpuSelection::Auto), Some(1));
+        assert_eq!(chosen(GpuSelection::Index(0)), Some(0));
+        assert_eq!(chosen(GpuSelection::Name("llvm".into())), Some(2));
+        assert_eq!(chosen(GpuSelection::Name("radeon".into())), None);
+        assert_eq!(chosen(GpuSelection::Index(3)), None);
+    }
+
+    #[test]
+    fn matches_selected_device() {
+        let name = "NVIDIA GeForce RTX 4090";
+
+        assert!(GpuSelection::Auto.matches(3, name));
+        assert!(GpuSelection::Index(3).matches(3, name));
+        assert!(!GpuSelection::Index(0).matches(3, name));
+        assert!(GpuSelection::Name("geforce".into()).matches(0, name));
+        assert!(!GpuSelection::Name("radeon".into()).matches(0, name));
+    }
+}
@@ -1,6 +1,18 @@
+mod config;
+mod gpu;
 mod vulkan;
 
-use raw_window_handle::HasRawWindowHandle;
+pub use self::config::{RendererConfig, VALIDATION_ENV_VAR};
+pub use self::gpu::{GpuSelection, GPU_ENV_VAR};
+pub use self::vulkan::{
+    DeviceFeatures, Error, PhysicalDeviceFeatures, PhysicalDeviceVulkan11Features,
+    PhysicalDeviceVulkan12Features, PhysicalDeviceVulkan13Features, PresentMode, Queue, QueueRole,
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+use ash::vk as raw;
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
 
 use self::vulkan as vk;
 
@@ -8,42 +20,477 @@ pub trait Render {
     fn render(&self);
 }
 
+#[allow(dead_code)]
 pub struct Renderer {
-    instance: vk::Instance,
-    physical_device: vk::PhysicalDevice,
-    device: vk::Device,
+    target: Target,
+    frame: Frame,
     queue: vk::Queue,
-    surface: vk::SurfaceKhr,
+    device: vk::Device,
+    physical_device: vk::PhysicalDevice,
+    instance: vk::Instance,
+    config: RendererConfig,
+    clear_color: [f32; 4],
+}
+
+/// Where frames end up: a window surface, or an image that stays on the GPU
+/// until it is read back.
+enum Target {
+    Surface(SurfaceTarget),
+    Offscreen(OffscreenTarget),
+}
+
+/// Per-frame objects. Frames are not overlapped yet, so there is only one set.
+struct Frame {
+    fence: vk::Fence,
+    /// Keeps its pool alive.
+    command_buffer: vk::CommandBuffer,
 }
 
 impl Renderer {
-    pub fn new<W: HasRawWindowHandle>(window: &W) -> Self {
-        let instance = vk::Instance::new().unwrap();
-        let surface = vk::SurfaceKhr::new(&instance, window);
-        let (queue_family_index, physical_device) = instance
-            .enumerate_physical_devices()
-            .find_map(|p| {
-                p.queue_family_properties()
-                    .iter()
-                    .enumerate()
-                    .find(|&(i, props)| props.graphics() && p.surface_support_khr(i, &surface))
-                    .map(|(i, _)| (i, p))
-            })
-            .unwrap();
-        log::info!("Using: {}", physical_device.device_name());
-        let device = vk::Device::new(&physical_device, queue_family_index);
-        let queue = device.get_queue(queue_family_index, 0);
+    /// Creates a renderer presenting to `window`, whose drawable area is
+    /// `width`x`height` pixels.
+    pub fn new<W: HasRawWindowHandle + HasRawDisplayHandle>(
+        window: &W,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        Self::with_config(window, width, height, RendererConfig::default())
+    }
 
-        Self {
+    pub fn with_config<W: HasRawWindowHandle + HasRawDisplayHandle>(
+        window: &W,
+        width: u32,
+        height: u32,
+        config: RendererConfig,
+    ) -> Result<Self> {
+        let instance = config.instance_builder().display(window).build()?;
+        let surface = vk::SurfaceKhr::new(&instance, window)?;
+        let (queue_families, physical_device) = gpu::select_physical_device(
+            &instance,
+            &GpuSelection::resolve(&config.gpu),
+            gpu::Requirements {
+                extensions: &config.device_extensions(&["VK_KHR_swapchain"]),
+                features: config.required_device_features,
+                surface: Some(&surface),
+            },
+        )?;
+
+        Self::with_target(
             instance,
             physical_device,
-            device,
+            queue_families,
+            &["VK_KHR_swapchain"],
+            config,
+            |device, physical_device, config| {
+                let extent = vk::Extent2D::new(width, height);
+                let swapchain = vk::SwapchainKhr::new(
+                    device,
+                    physical_device,
+                    &surface,
+                    extent,
+                    &config.present_modes,
+                )?;
+                swapchain.set_debug_name("swapchain")?;
+                let image_available = vk::Semaphore::new(device)?;
+                image_available.set_debug_name("image available")?;
+                let render_finished = vk::Semaphore::new(device)?;
+                render_finished.set_debug_name("render finished")?;
+                let present_queue = device
+                    .queue(vk::QueueRole::Present)
+                    .expect("Device was created with a present queue");
+
+                Ok(Target::Surface(SurfaceTarget {
+                    swapchain,
+                    present_queue,
+                    image_available,
+                    render_finished,
+                    surface,
+                    extent,
+                    out_of_date: false,
+                }))
+            },
+        )
+    }
+
+    /// Creates a renderer without a window. Frames are drawn into a
+    /// `width`x`height` RGBA8 image that can be read back with
+    /// [`read_pixels`](Self::read_pixels). Fails with [`Error::InvalidExtent`]
+    /// if either side is zero.
+    pub fn new_headless(width: u32, height: u32) -> Result<Self> {
+        Self::new_headless_with_config(width, height, RendererConfig::default())
+    }
+
+    pub fn new_headless_with_config(
+        width: u32,
+        height: u32,
+        config: RendererConfig,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidExtent { width, height });
+        }
+
+        let instance = config.instance_builder().build()?;
+        let (queue_families, physical_device) = gpu::select_physical_device(
+            &instance,
+            &GpuSelection::resolve(&config.gpu),
+            gpu::Requirements {
+                extensions: &config.device_extensions(&[]),
+                features: config.required_device_features,
+                surface: None,
+            },
+        )?;
+
+        Self::with_target(
+            instance,
+            physical_device,
+            queue_families,
+            &[],
+            config,
+            |device, _, _| OffscreenTarget::new(device, width, height).map(Target::Offscreen),
+        )
+    }
+
+    fn with_target(
+        instance: vk::Instance,
+        physical_device: vk::PhysicalDevice,
+        queue_families: vk::QueueFamilies,
+        renderer_extensions: &[&str],
+        config: RendererConfig,
+        target: impl FnOnce(&vk::Device, &vk::PhysicalDevice, &RendererConfig) -> Result<Target>,
+    ) -> Result<Self> {
+        log::info!("Using: {}", physical_device.device_name());
+        log::info!("Queue families: {:?}", queue_families);
+        let device = vk::DeviceBuilder::new(&physical_device, queue_families)
+            .required_extensions(config.device_extensions(renderer_extensions))
+            .optional_extensions(&config.optional_device_extensions)
+            .required_features(config.required_device_features)
+            .optional_features(config.optional_device_features)
+            .build()?;
+        log::info!("Device extensions: {:?}", device.enabled_extensions());
+        device.set_debug_name("device")?;
+        let queue = device
+            .queue(vk::QueueRole::Graphics)
+            .expect("Device was created with a graphics queue");
+        queue.set_debug_name("graphics queue")?;
+        // Queues of roles that share the graphics family keep its name.
+        for (role, name) in [
+            (vk::QueueRole::Present, "present queue"),
+            (vk::QueueRole::Compute, "compute queue"),
+            (vk::QueueRole::Transfer, "transfer queue"),
+        ] {
+            if let Some(other) = device.queue(role) {
+                if other.family_index() != queue.family_index() {
+                    other.set_debug_name(name)?;
+                }
+            }
+        }
+        let command_pool = vk::CommandPool::new(&device, queue.family_index())?;
+        command_pool.set_debug_name("frame command pool")?;
+        let frame = Frame {
+            fence: vk::Fence::new(&device, false)?,
+            command_buffer: command_pool.allocate()?,
+        };
+        frame.fence.set_debug_name("frame fence")?;
+        frame
+            .command_buffer
+            .set_debug_name("frame command buffer")?;
+        let target = target(&device, &physical_device, &config)?;
+
+        let renderer = Self {
+            target,
+            frame,
             queue,
-            surface,
+            device,
+            physical_device,
+            instance,
+            config,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        };
+        renderer.check_validation();
+
+        Ok(renderer)
+    }
+
+    /// Panics if validation errors were reported and the renderer was configured
+    /// to panic on them.
+    fn check_validation(&self) {
+        let errors = self.instance.take_validation_errors();
+        if !errors.is_empty() {
+            panic!("Validation error(s):\n{}", errors.join("\n"));
+        }
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: [f32; 4]) {
+        self.clear_color = clear_color;
+    }
+
+    /// Replaces the present mode preference of the config. The swapchain is
+    /// recreated before the next frame.
+    pub fn set_present_modes(&mut self, present_modes: impl IntoIterator<Item = PresentMode>) {
+        self.config.present_modes = present_modes.into_iter().collect();
+        if let Target::Surface(target) = &mut self.target {
+            target.out_of_date = true;
+        }
+    }
+
+    /// The present mode in use, or `None` for a headless renderer.
+    pub fn present_mode(&self) -> Option<PresentMode> {
+        match &self.target {
+            Target::Surface(target) => Some(target.swapchain.present_mode()),
+            Target::Offscreen(_) => None,
+        }
+    }
+
+    /// Must be called whenever the drawable area of the window changes. The
+    /// swapchain is recreated before the next frame; while either side is zero,
+    /// e.g. when the window is minimised, no frames are drawn.
+    ///
+    /// A headless renderer replaces its image, discarding the last frame. It
+    /// keeps its current image when either side is zero, since an image cannot
+    /// be empty.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        match &mut self.target {
+            Target::Surface(target) => {
+                let extent = vk::Extent2D::new(width, height);
+                if target.extent != extent {
+                    target.extent = extent;
+                    target.out_of_date = true;
+                }
+            }
+            Target::Offscreen(target) => {
+                if width != 0
+                    && height != 0
+                    && target.image.extent() != (raw::Extent2D { width, height })
+                {
+                    *target = OffscreenTarget::new(&self.device, width, height)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draws a frame, or does nothing while the window is minimised. A
+    /// swapchain that became out of date is recreated on the next call.
+    pub fn draw_frame(&mut self) -> Result<()> {
+        match &mut self.target {
+            Target::Surface(target) => {
+                if !target.prepare(&self.physical_device, &self.config.present_modes)? {
+                    return Ok(());
+                }
+
+                match target.draw(&self.queue, &self.frame, self.clear_color) {
+                    Ok(suboptimal) => target.out_of_date |= suboptimal,
+                    Err(e) if e.is_out_of_date() => target.out_of_date = true,
+                    Err(e) => return Err(e),
+                }
+            }
+            Target::Offscreen(target) => {
+                target.draw(&self.queue, &self.frame, self.clear_color)?;
+            }
+        }
+
+        self.check_validation();
+        Ok(())
+    }
+
+    /// The last frame drawn by a headless renderer as tightly packed RGBA8 rows.
+    /// Returns `None` when rendering to a window.
+    pub fn read_pixels(&self) -> Result<Option<Vec<u8>>> {
+        match &self.target {
+            Target::Surface(_) => Ok(None),
+            Target::Offscreen(target) => target.readback.read().map(Some),
+        }
+    }
+
+    /// Whether `extension` was enabled on the device, either because it is
+    /// required or because it is optional and supported.
+    pub fn is_device_extension_enabled(&self, extension: &str) -> bool {
+        self.device.is_extension_enabled(extension)
+    }
+
+    /// The required device features and the optional ones that are supported.
+    pub fn enabled_device_features(&self) -> &DeviceFeatures {
+        self.device.enabled_features()
+    }
+
+    /// Size of the images frames are drawn into.
+    pub fn extent(&self) -> (u32, u32) {
+        let extent = match &self.target {
+            Target::Surface(target) => target.swapchain.extent(),
+            Target::Offscreen(target) => target.image.extent(),
+        };
+
+        (extent.width, extent.height)
+    }
+
+    /// The queue created for `role`. `None` only for [`QueueRole::Present`]
+    /// when rendering headless.
+    pub fn queue(&self, role: QueueRole) -> Option<Queue> {
+        self.device.queue(role)
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        // Resources may still be in use by a submitted frame.
+        let _ = self.device.wait_idle();
+    }
+}
+
+struct SurfaceTarget {
+    swapchain: vk::SwapchainKhr,
+    /// The graphics queue unless the device presents from another family.
+    present_queue: vk::Queue,
+    image_available: vk::Semaphore,
+    render_finished: vk::Semaphore,
+    surface: vk::SurfaceKhr,
+    /// Size of the window, used when the surface leaves it to the swapchain.
+    extent: vk::Extent2D,
+    /// Set when the swapchain has to be recreated before the next frame.
+    out_of_date: bool,
+}
+
+impl SurfaceTarget {
+    /// Recreates the swapchain if needed. Returns `false` if there is nothing to
+    /// draw into at the moment.
+    fn prepare(
+        &mut self,
+        physical_device: &vk::PhysicalDevice,
+        present_modes: &[PresentMode],
+    ) -> Result<bool> {
+        if self.extent.width() == 0 || self.extent.height() == 0 {
+            return Ok(false);
+        }
+        if !self.out_of_date {
+            return Ok(true);
         }
+
+        let capabilities = physical_device.surface_capabilities(&self.surface)?;
+        if let Some(current) = capabilities.current_extent() {
+            if current.width() == 0 || current.height() == 0 {
+                return Ok(false);
+            }
+        }
+
+        // Every submitted frame has been waited on, so the old swapchain is only
+        // in use by the presentation engine, which `recreate` accounts for.
+        self.swapchain =
+            self.swapchain
+                .recreate(physical_device, &self.surface, self.extent, present_modes)?;
+        self.swapchain.set_debug_name("swapchain")?;
+        self.out_of_date = false;
+        log::trace!("Swapchain recreated.");
+
+        Ok(true)
+    }
+
+    /// Returns whether the swapchain should be recreated.
+    fn draw(&self, queue: &vk::Queue, frame: &Frame, clear_color: [f32; 4]) -> Result<bool> {
+        let (index, suboptimal) = self.swapchain.acquire_next_image(&self.image_available)?;
+        let image = &self.swapchain.images()[index];
+
+        let cmd = &frame.command_buffer;
+        cmd.begin()?;
+        cmd.begin_label("clear", [0.2, 0.4, 1.0, 1.0]);
+        cmd.image_barrier(
+            image,
+            raw::ImageLayout::UNDEFINED,
+            raw::ImageLayout::TRANSFER_DST_OPTIMAL,
+            raw::PipelineStageFlags::TRANSFER,
+            raw::AccessFlags::empty(),
+            raw::PipelineStageFlags::TRANSFER,
+            raw::AccessFlags::TRANSFER_WRITE,
+        );
+        cmd.clear_color_image(image, clear_color);
+        cmd.image_barrier(
+            image,
+            raw::ImageLayout::TRANSFER_DST_OPTIMAL,
+            raw::ImageLayout::PRESENT_SRC_KHR,
+            raw::PipelineStageFlags::TRANSFER,
+            raw::AccessFlags::TRANSFER_WRITE,
+            raw::PipelineStageFlags::BOTTOM_OF_PIPE,
+            raw::AccessFlags::empty(),
+        );
+        cmd.end_label();
+        cmd.end()?;
+
+        queue.submit(
+            cmd,
+            &[(&self.image_available, raw::PipelineStageFlags::TRANSFER)],
+            &[&self.render_finished],
+            Some(&frame.fence),
+        )?;
+        let presented = self
+            .swapchain
+            .present(&self.present_queue, index, &self.render_finished);
+        // Wait even if presenting failed, the command buffer is reused next frame.
+        frame.fence.wait()?;
+        frame.fence.reset()?;
+
+        Ok(suboptimal | presented?)
+    }
+}
+
+struct OffscreenTarget {
+    image: vk::Image,
+    readback: vk::Buffer,
+}
+
+impl OffscreenTarget {
+    fn new(device: &vk::Device, width: u32, height: u32) -> Result<Self> {
+        let image = vk::Image::new(
+            device,
+            raw::Extent2D { width, height },
+            raw::Format::R8G8B8A8_UNORM,
+            raw::ImageUsageFlags::COLOR_ATTACHMENT
+                | raw::ImageUsageFlags::TRANSFER_SRC
+                | raw::ImageUsageFlags::TRANSFER_DST,
+        )?;
+        let readback = vk::Buffer::new(
+            device,
+            u64::from(width) * u64::from(height) * 4,
+            raw::BufferUsageFlags::TRANSFER_DST,
+            raw::MemoryPropertyFlags::HOST_VISIBLE | raw::MemoryPropertyFlags::HOST_COHERENT,
+        )?;
+        image.set_debug_name("offscreen image")?;
+        readback.set_debug_name("readback buffer")?;
+
+        Ok(Self { image, readback })
     }
 
-    pub fn draw_frame(&self) {
-        println!("drawing");
+    fn draw(&self, queue: &vk::Queue, frame: &Frame, clear_color: [f32; 4]) -> Result<()> {
+        let cmd = &frame.command_buffer;
+        cmd.begin()?;
+        cmd.begin_label("clear", [0.2, 0.4, 1.0, 1.0]);
+        cmd.image_barrier(
+            &self.image,
+            raw::ImageLayout::UNDEFINED,
+            raw::ImageLayout::TRANSFER_DST_OPTIMAL,
+            raw::PipelineStageFlags::TOP_OF_PIPE,
+            raw::AccessFlags::empty(),
+            raw::PipelineStageFlags::TRANSFER,
+            raw::AccessFlags::TRANSFER_WRITE,
+        );
+        cmd.clear_color_image(&self.image, clear_color);
+        cmd.end_label();
+        cmd.begin_label("readback", [1.0, 0.6, 0.2, 1.0]);
+        cmd.image_barrier(
+            &self.image,
+            raw::ImageLayout::TRANSFER_DST_OPTIMAL,
+            raw::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            raw::PipelineStageFlags::TRANSFER,
+            raw::AccessFlags::TRANSFER_WRITE,
+            raw::PipelineStageFlags::TRANSFER,
+            raw::AccessFlags::TRANSFER_READ,
+        );
+        cmd.copy_image_to_buffer(&self.image, &self.readback);
+        cmd.host_read_barrier(&self.readback);
+        cmd.end_label();
+        cmd.end()?;
+
+        queue.submit(cmd, &[], &[], Some(&frame.fence))?;
+        frame.fence.wait()?;
+        frame.fence.reset()
     }
 }
@@ -0,0 +1,260 @@
+use ash::vk;
+
+use super::{Buffer, Device, Image, Result};
+
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct CommandPool {
+    inner: Arc<RawCommandPool>,
+}
+
+impl CommandPool {
+    /// A pool whose command buffers can be reset individually.
+    pub fn new(device: &Device, queue_family_index: usize) -> Result<Self> {
+        let create_info = vk::CommandPoolCreateInfo {
+            flags: vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER,
+            queue_family_index: queue_family_index
+                .try_into()
+                .expect("Could not convert `usize` to `u32`"),
+            ..Default::default()
+        };
+
+        let handle = unsafe {
+            device
+                .inner
+                .handle
+                .create_command_pool(&create_info, None)?
+        };
+
+        Ok(Self {
+            inner: Arc::new(RawCommandPool {
+                handle,
+                device: device.clone(),
+            }),
+        })
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.inner.device.set_object_name(self.inner.handle, name)
+    }
+
+    pub fn allocate(&self) -> Result<CommandBuffer> {
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            command_pool: self.inner.handle,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            ..Default::default()
+        };
+
+        let handle = unsafe {
+            self.inner
+                .device
+                .inner
+                .handle
+                .allocate_command_buffers(&allocate_info)?[0]
+        };
+
+        Ok(CommandBuffer {
+            handle,
+            pool: self.clone(),
+        })
+    }
+}
+
+struct RawCommandPool {
+    handle: vk::CommandPool,
+    device: Device,
+}
+
+impl Drop for RawCommandPool {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .inner
+                .handle
+                .destroy_command_pool(self.handle, None)
+        };
+    }
+}
+
+/// A primary command buffer. Recording methods must be called between
+/// [`begin`](Self::begin) and [`end`](Self::end).
+pub struct CommandBuffer {
+    pub(super) handle: vk::CommandBuffer,
+    pool: CommandPool,
+}
+
+impl CommandBuffer {
+    fn device(&self) -> &ash::Device {
+        &self.pool.inner.device.inner.handle
+    }
+
+    /// Resets the command buffer and starts recording for a single submission.
+    pub fn begin(&self) -> Result<()> {
+        let begin_info = vk::CommandBufferBeginInfo {
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            ..Default::default()
+        };
+
+        unsafe {
+            self.device()
+                .reset_command_buffer(self.handle, vk::CommandBufferResetFlags::empty())?;
+            self.device()
+                .begin_command_buffer(self.handle, &begin_info)?;
+        }
+        Ok(())
+    }
+
+    pub fn end(&self) -> Result<()> {
+        unsafe { self.device().end_command_buffer(self.handle)? };
+        Ok(())
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.pool.inner.device.set_object_name(self.handle, name)
+    }
+
+    /// Opens a labelled region that lasts until the matching
+    /// [`end_label`](Self::end_label). Regions can be nested. Does nothing
+    /// unless `VK_EXT_debug_utils` is enabled.
+    pub fn begin_label(&self, name: &str, color: [f32; 4]) {
+        if let Some(fp) = &self.pool.inner.device.inner.debug_utils {
+            // SAFETY: Recording methods are only valid between `begin` and `end`.
+            unsafe { fp.begin_label(self.handle, name, color) };
+        }
+    }
+
+    pub fn end_label(&self) {
+        if let Some(fp) = &self.pool.inner.device.inner.debug_utils {
+            // SAFETY: Recording methods are only valid between `begin` and `end`.
+            unsafe { fp.end_label(self.handle) };
+        }
+    }
+
+    /// Transitions the single color subresource of `image` between layouts.
+    #[allow(clippy::too_many_arguments)]
+    pub fn image_barrier(
+        &self,
+        image: &Image,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+        src_stage: vk::PipelineStageFlags,
+        src_access: vk::AccessFlags,
+        dst_stage: vk::PipelineStageFlags,
+        dst_access: vk::AccessFlags,
+    ) {
+        let barrier = vk::ImageMemoryBarrier {
+            src_access_mask: src_access,
+            dst_access_mask: dst_access,
+            old_layout,
+            new_layout,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            image: image.handle,
+            subresource_range: COLOR_SUBRESOURCE_RANGE,
+            ..Default::default()
+        };
+
+        unsafe {
+            self.device().cmd_pipeline_barrier(
+                self.handle,
+                src_stage,
+                dst_stage,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[barrier],
+            )
+        };
+    }
+
+    /// Makes transfer writes to `buffer` visible to the host.
+    pub fn host_read_barrier(&self, buffer: &Buffer) {
+        let barrier = vk::BufferMemoryBarrier {
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: vk::AccessFlags::HOST_READ,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            buffer: buffer.handle,
+            offset: 0,
+            size: vk::WHOLE_SIZE,
+            ..Default::default()
+        };
+
+        unsafe {
+            self.device().cmd_pipeline_barrier(
+                self.handle,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[barrier],
+                &[],
+            )
+        };
+    }
+
+    /// `image` must be in `TRANSFER_DST_OPTIMAL` layout.
+    pub fn clear_color_image(&self, image: &Image, color: [f32; 4]) {
+        unsafe {
+            self.device().cmd_clear_color_image(
+                self.handle,
+                image.handle,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &vk::ClearColorValue { float32: color },
+                &[COLOR_SUBRESOURCE_RANGE],
+            )
+        };
+    }
+
+    /// Copies the whole of `image`, which must be in `TRANSFER_SRC_OPTIMAL`
+    /// layout, tightly packed into `buffer`.
+    pub fn copy_image_to_buffer(&self, image: &Image, buffer: &Buffer) {
+        let extent = image.extent();
+        let region = vk::BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_subresource: vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            image_offset: vk::Offset3D::default(),
+            image_extent: vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+        };
+
+        unsafe {
+            self.device().cmd_copy_image_to_buffer(
+                self.handle,
+                image.handle,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                buffer.handle,
+                &[region],
+            )
+        };
+    }
+}
+
+impl Drop for CommandBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.device()
+                .free_command_buffers(self.pool.inner.handle, &[self.handle])
+        };
+    }
+}
+
+const COLOR_SUBRESOURCE_RANGE: vk::ImageSubresourceRange = vk::ImageSubresourceRange {
+    aspect_mask: vk::ImageAspectFlags::COLOR,
+    base_mip_level: 0,
+    level_count: 1,
+    base_array_layer: 0,
+    layer_count: 1,
+};
@@ -0,0 +1,253 @@
+use ash::vk;
+
+use super::{Error, Result};
+
+use std::ffi::{c_void, CStr, CString};
+use std::mem::MaybeUninit;
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+/// Name of the Khronos validation layer.
+pub const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+/// Whether `VK_LAYER_KHRONOS_validation` and a debug messenger are enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validation {
+    Disabled,
+    /// Messages are forwarded to `log`.
+    Enabled,
+    /// Messages are forwarded to `log`, and validation errors are also kept so
+    /// that the caller can panic on them. Meant for tests.
+    PanicOnError,
+}
+
+impl Validation {
+    pub fn is_enabled(&self) -> bool {
+        *self != Self::Disabled
+    }
+}
+
+dynamic_library::api! {
+    struct DebugUtilsFn {
+        create_debug_utils_messenger_ext: vk::PFN_vkCreateDebugUtilsMessengerEXT =
+            "vkCreateDebugUtilsMessengerEXT",
+        destroy_debug_utils_messenger_ext: vk::PFN_vkDestroyDebugUtilsMessengerEXT =
+            "vkDestroyDebugUtilsMessengerEXT",
+    }
+}
+
+dynamic_library::api! {
+    pub(super) struct DebugUtilsDeviceFn {
+        set_debug_utils_object_name_ext: vk::PFN_vkSetDebugUtilsObjectNameEXT =
+            "vkSetDebugUtilsObjectNameEXT",
+        cmd_begin_debug_utils_label_ext: vk::PFN_vkCmdBeginDebugUtilsLabelEXT =
+            "vkCmdBeginDebugUtilsLabelEXT",
+        cmd_end_debug_utils_label_ext: vk::PFN_vkCmdEndDebugUtilsLabelEXT =
+            "vkCmdEndDebugUtilsLabelEXT",
+    }
+}
+
+impl DebugUtilsDeviceFn {
+    /// Names `handle` so that validation messages and captures refer to it by
+    /// `name`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a live object created from `device`.
+    pub(super) unsafe fn set_object_name<H: vk::Handle>(
+        &self,
+        device: vk::Device,
+        handle: H,
+        name: &str,
+    ) -> Result<()> {
+        let name = debug_name(name);
+        let name_info = vk::DebugUtilsObjectNameInfoEXT {
+            object_type: H::TYPE,
+            object_handle: handle.as_raw(),
+            p_object_name: name.as_ptr(),
+            ..Default::default()
+        };
+
+        match (self.set_debug_utils_object_name_ext)(device, &name_info) {
+            vk::Result::SUCCESS => Ok(()),
+            code => Err(code.into()),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `command_buffer` must be in the recording state.
+    pub(super) unsafe fn begin_label(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        name: &str,
+        color: [f32; 4],
+    ) {
+        let name = debug_name(name);
+        let label = vk::DebugUtilsLabelEXT {
+            p_label_name: name.as_ptr(),
+            color,
+            ..Default::default()
+        };
+
+        (self.cmd_begin_debug_utils_label_ext)(command_buffer, &label);
+    }
+
+    /// # Safety
+    ///
+    /// `command_buffer` must be in the recording state with an open label.
+    pub(super) unsafe fn end_label(&self, command_buffer: vk::CommandBuffer) {
+        (self.cmd_end_debug_utils_label_ext)(command_buffer);
+    }
+}
+
+/// Interior nul bytes are dropped rather than failing, names are only a
+/// debugging aid.
+fn debug_name(name: &str) -> CString {
+    CString::new(name.replace('\0', "")).expect("Nul bytes were removed")
+}
+
+/// Shared with the callback through its user data pointer, so it has to stay
+/// at the same address for as long as the messenger lives.
+pub(super) struct MessengerState {
+    keep_errors: bool,
+    errors: Mutex<Vec<String>>,
+}
+
+impl MessengerState {
+    pub(super) fn new(validation: Validation) -> Box<Self> {
+        Box::new(Self {
+            keep_errors: validation == Validation::PanicOnError,
+            errors: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Also used to receive messages about instance creation and destruction.
+    pub(super) fn create_info(&self) -> vk::DebugUtilsMessengerCreateInfoEXT {
+        vk::DebugUtilsMessengerCreateInfoEXT {
+            message_severity: vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE
+                | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
+                | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
+                | vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+            message_type: vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
+                | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
+                | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
+            pfn_user_callback: Some(debug_callback),
+            p_user_data: self as *const Self as *mut c_void,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct DebugMessenger {
+    handle: vk::DebugUtilsMessengerEXT,
+    fp: DebugUtilsFn,
+    state: Box<MessengerState>,
+}
+
+impl DebugMessenger {
+    /// # Safety
+    ///
+    /// `instance` must have been created with `VK_EXT_debug_utils` enabled and
+    /// `loader` must return its commands.
+    ///
+    /// On failure `state` is handed back, since a messenger chained into the
+    /// instance create info may still report through it until the instance is
+    /// destroyed.
+    pub(super) unsafe fn new(
+        instance: vk::Instance,
+        loader: impl FnMut(&CStr) -> Option<NonNull<()>>,
+        state: Box<MessengerState>,
+    ) -> std::result::Result<Self, (Error, Box<MessengerState>)> {
+        let fp = match DebugUtilsFn::load_with(loader) {
+            Ok(fp) => fp,
+            Err(e) => return Err((e.into(), state)),
+        };
+
+        let create_info = state.create_info();
+        let mut handle = MaybeUninit::uninit();
+        let res = (fp.create_debug_utils_messenger_ext)(
+            instance,
+            &create_info,
+            std::ptr::null(),
+            handle.as_mut_ptr(),
+        );
+        if res != vk::Result::SUCCESS {
+            return Err((Error::from(res), state));
+        }
+
+        log::trace!("Debug messenger created.");
+        Ok(Self {
+            handle: handle.assume_init(),
+            fp,
+            state,
+        })
+    }
+
+    /// # Safety
+    ///
+    /// `instance` must be the instance the messenger was created for, and the
+    /// messenger must not be used afterwards.
+    pub(super) unsafe fn destroy(&mut self, instance: vk::Instance) {
+        (self.fp.destroy_debug_utils_messenger_ext)(instance, self.handle, std::ptr::null());
+        log::trace!("Debug messenger destroyed.");
+    }
+
+    /// Validation errors reported since the last call, if they are kept.
+    pub(super) fn take_errors(&self) -> Vec<String> {
+        self.state
+            .errors
+            .lock()
+            .map(|mut errors| std::mem::take(&mut *errors))
+            .unwrap_or_default()
+    }
+}
+
+unsafe extern "system" fn debug_callback(
+    severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    types: vk::DebugUtilsMessageTypeFlagsEXT,
+    data: *const vk::DebugUtilsMessengerCallbackDataEXT,
+    user_data: *mut c_void,
+) -> vk::Bool32 {
+    // SAFETY: The implementation passes valid callback data, and `user_data` is
+    // the `MessengerState` given in `MessengerState::create_info`.
+    let (message, state) = unsafe {
+        let data = &*data;
+        let message = if data.p_message.is_null() {
+            "".into()
+        } else {
+            CStr::from_ptr(data.p_message).to_string_lossy()
+        };
+
+        (message, &*(user_data as *const MessengerState))
+    };
+
+    let level = if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR) {
+        log::Level::Error
+    } else if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING) {
+        log::Level::Warn
+    } else if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::INFO) {
+        log::Level::Info
+    } else {
+        log::Level::Trace
+    };
+    let kind = if types.contains(vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION) {
+        "validation"
+    } else if types.contains(vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE) {
+        "performance"
+    } else {
+        "general"
+    };
+    log::log!(target: "vulkan", level, "[{}] {}", kind, message);
+
+    if state.keep_errors
+        && level == log::Level::Error
+        && types.contains(vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION)
+    {
+        if let Ok(mut errors) = state.errors.lock() {
+            errors.push(message.into_owned());
+        }
+    }
+
+    vk::FALSE
+}
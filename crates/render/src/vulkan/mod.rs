@@ -1,32 +1,177 @@
 #![allow(dead_code)]
 
+mod command;
+mod debug;
+mod properties;
+mod resource;
+mod surface;
+mod sync;
+
+pub use self::command::{CommandBuffer, CommandPool};
+pub use self::debug::Validation;
+pub use self::properties::*;
+pub use self::resource::{Buffer, Image, ImageView};
+pub use self::surface::*;
+pub use self::sync::{Fence, Semaphore};
+
 use ash::vk;
-use raw_window_handle::HasRawWindowHandle;
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle};
+
+use self::debug::{DebugMessenger, DebugUtilsDeviceFn, MessengerState, VALIDATION_LAYER};
 
-use dynamic_library::DynamicLibrary;
+use dynamic_library::{DynamicLibrary, MissingSymbols, Search};
 
-use std::ffi::{CStr, CString};
+use std::ffi::{c_void, CStr, CString};
 use std::mem::MaybeUninit;
-use std::sync::Arc;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The Vulkan loader could not be loaded.
+    Loader(dynamic_library::Error),
+    /// Commands of an enabled extension could not be resolved.
+    MissingCommands(MissingSymbols),
+    /// The loader does not support the lowest Vulkan version required.
+    UnsupportedApiVersion { required: u32, supported: u32 },
+    /// Required instance extensions that are not supported and required layers
+    /// that are not installed.
+    MissingInstanceRequirements {
+        extensions: Vec<String>,
+        layers: Vec<String>,
+    },
+    /// Required device extensions and features that are not supported.
+    MissingDeviceRequirements {
+        extensions: Vec<String>,
+        features: Vec<&'static str>,
+    },
+    /// The instance does not report any physical device.
+    NoPhysicalDevice,
+    /// No physical device has a queue family with the described capabilities.
+    MissingQueueFamily(&'static str),
+    /// No physical device matches an explicit selection by name or index.
+    DeviceNotFound(String),
+    /// There is no surface extension for the display or window.
+    UnsupportedWindow(String),
+    /// The surface does not support an image usage the renderer relies on.
+    UnsupportedSurfaceUsage(&'static str),
+    /// An image would be empty because its width or height is zero.
+    InvalidExtent { width: u32, height: u32 },
+    /// A Vulkan command failed.
+    Vk(vk::Result),
+}
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct Error {
-    code: vk::Result,
+impl Error {
+    /// The surface changed so that the swapchain can no longer present to it.
+    pub fn is_out_of_date(&self) -> bool {
+        *self == Self::Vk(vk::Result::ERROR_OUT_OF_DATE_KHR)
+    }
 }
 
 impl From<vk::Result> for Error {
     fn from(code: vk::Result) -> Self {
-        Self { code }
+        Self::Vk(code)
+    }
+}
+
+impl From<dynamic_library::Error> for Error {
+    fn from(e: dynamic_library::Error) -> Self {
+        Self::Loader(e)
+    }
+}
+
+impl From<MissingSymbols> for Error {
+    fn from(e: MissingSymbols) -> Self {
+        Self::MissingCommands(e)
     }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}. ({})", self.code, self.code.as_raw())
+        match self {
+            Self::Loader(e) => write!(f, "Could not load Vulkan: {}", e),
+            Self::MissingCommands(e) => write!(f, "{}", e),
+            Self::UnsupportedApiVersion {
+                required,
+                supported,
+            } => {
+                write!(
+                    f,
+                    "Vulkan {}.{} is required, but the loader only supports {}.{}",
+                    vk::api_version_major(*required),
+                    vk::api_version_minor(*required),
+                    vk::api_version_major(*supported),
+                    vk::api_version_minor(*supported)
+                )
+            }
+            Self::MissingInstanceRequirements { extensions, layers } => {
+                write!(f, "Required instance support is missing:")?;
+                if !extensions.is_empty() {
+                    write!(f, " extensions {}", extensions.join(", "))?;
+                }
+                if !extensions.is_empty() && !layers.is_empty() {
+                    write!(f, ";")?;
+                }
+                if !layers.is_empty() {
+                    write!(f, " layers {}", layers.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::MissingDeviceRequirements {
+                extensions,
+                features,
+            } => {
+                write!(f, "Required device support is missing:")?;
+                if !extensions.is_empty() {
+                    write!(f, " extensions {}", extensions.join(", "))?;
+                }
+                if !extensions.is_empty() && !features.is_empty() {
+                    write!(f, ";")?;
+                }
+                if !features.is_empty() {
+                    write!(f, " features {}", features.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::NoPhysicalDevice => write!(f, "Could not find any physical device"),
+            Self::MissingQueueFamily(capabilities) => {
+                write!(
+                    f,
+                    "Could not find a queue family supporting {}",
+                    capabilities
+                )
+            }
+            Self::DeviceNotFound(selection) => {
+                write!(
+                    f,
+                    "Could not find a physical device matching `{}`",
+                    selection
+                )
+            }
+            Self::UnsupportedWindow(window) => write!(f, "Unsupported window: {}", window),
+            Self::UnsupportedSurfaceUsage(usage) => {
+                write!(f, "The surface does not support {} swapchain images", usage)
+            }
+            Self::InvalidExtent { width, height } => {
+                write!(
+                    f,
+                    "Invalid extent {}x{}, both sides must be non-zero",
+                    width, height
+                )
+            }
+            Self::Vk(code) => write!(f, "{}. ({})", code, code.as_raw()),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Loader(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -35,74 +180,326 @@ pub struct Instance {
     inner: Arc<RawInstance>,
 }
 
-impl Instance {
-    pub fn new() -> Result<Self> {
+/// Creates an [`Instance`] for the highest Vulkan version that both the loader
+/// and the application support. Required extensions and layers must all be
+/// available, optional ones are enabled only where they are.
+#[derive(Debug, Clone)]
+pub struct InstanceBuilder {
+    app_name: String,
+    app_version: u32,
+    min_api_version: u32,
+    max_api_version: u32,
+    display: Option<RawDisplayHandle>,
+    validation: Validation,
+    portability_enumeration: bool,
+    required_extensions: Vec<String>,
+    optional_extensions: Vec<String>,
+    required_layers: Vec<String>,
+    optional_layers: Vec<String>,
+}
+
+impl Default for InstanceBuilder {
+    fn default() -> Self {
+        Self {
+            app_name: String::from("Tanelorn"),
+            app_version: vk::make_api_version(0, 0, 1, 0),
+            min_api_version: vk::API_VERSION_1_0,
+            max_api_version: vk::API_VERSION_1_3,
+            display: None,
+            validation: Validation::Disabled,
+            portability_enumeration: false,
+            required_extensions: Vec::new(),
+            optional_extensions: Vec::new(),
+            required_layers: Vec::new(),
+            optional_layers: Vec::new(),
+        }
+    }
+}
+
+impl InstanceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn app_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.app_name = name.into();
+        self
+    }
+
+    /// As made by `vk::make_api_version`.
+    pub fn app_version(mut self, version: u32) -> Self {
+        self.app_version = version;
+        self
+    }
+
+    /// Building fails if the loader does not support at least this version.
+    pub fn min_api_version(mut self, version: u32) -> Self {
+        self.min_api_version = version;
+        self
+    }
+
+    /// The instance is created for this version at most. Defaults to 1.3.
+    pub fn max_api_version(mut self, version: u32) -> Self {
+        self.max_api_version = version;
+        self
+    }
+
+    /// Requires the surface extensions needed to present to windows of
+    /// `display`. Building fails if there are none.
+    pub fn display<D: HasRawDisplayHandle>(mut self, display: &D) -> Self {
+        self.display = Some(display.raw_display_handle());
+        self
+    }
+
+    /// Enables `VK_LAYER_KHRONOS_validation` and `VK_EXT_debug_utils` where they
+    /// are available.
+    pub fn validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Also enumerates devices that do not fully conform to the specification,
+    /// such as those layered on top of another API.
+    pub fn portability_enumeration(mut self, enabled: bool) -> Self {
+        self.portability_enumeration = enabled;
+        self
+    }
+
+    pub fn required_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn optional_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.optional_extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn required_layers<I, S>(mut self, layers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_layers
+            .extend(layers.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn optional_layers<I, S>(mut self, layers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.optional_layers
+            .extend(layers.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(&self) -> Result<Instance> {
         #[cfg(windows)]
-        let path = "vulkan-1.dll";
-        #[cfg(not(all(windows, target_pointer_width = "64")))]
-        compile_error!("Only Windows 64-bit is supported");
-
-        let Some(lib) = DynamicLibrary::new(path) else {
-            log::error!("Could not load dynamic library.");
-            panic!()
-        };
-        let Some(get_instance_proc_addr) = lib
-            .get("vkGetInstanceProcAddr")
-            .map(|sym| unsafe { std::mem::transmute(sym.as_ptr()) }) else {
-                log::error!("Could not retrieve symbol `vkGetInstanceProcAddr`.");
-                panic!()
-            };
+        let candidates = ["vulkan-1.dll"];
+        #[cfg(all(unix, not(target_vendor = "apple")))]
+        let candidates = ["libvulkan.so.1", "libvulkan.so"];
+        #[cfg(not(any(windows, all(unix, not(target_vendor = "apple")))))]
+        compile_error!("Only Windows and Linux are supported");
+
+        let lib = load_vulkan_library(&candidates)?;
+        match lib.path() {
+            Ok(path) => log::info!("Vulkan loader: {}", path.display()),
+            Err(e) => log::warn!("{}", e),
+        }
+        // SAFETY: `vkGetInstanceProcAddr` has the signature of `PFN_vkGetInstanceProcAddr`.
+        let get_instance_proc_addr =
+            *unsafe { lib.get_typed::<vk::PFN_vkGetInstanceProcAddr>("vkGetInstanceProcAddr")? };
 
         let static_fn = ash::vk::StaticFn {
             get_instance_proc_addr,
         };
         let entry = unsafe { ash::Entry::from_static_fn(static_fn) };
 
-        let mut required_extensions = vec!["VK_KHR_surface", "VK_KHR_win32_surface"];
-        if cfg!(debug_assertions) {
-            log::info!("Debug utilities extension enabled.");
-            required_extensions.push("VK_EXT_debug_utils");
-        }
+        // Loaders without `vkEnumerateInstanceVersion` only support Vulkan 1.0.
+        let loader_version = entry
+            .try_enumerate_instance_version()?
+            .unwrap_or(vk::API_VERSION_1_0);
+        let api_version =
+            negotiate_api_version(loader_version, self.min_api_version, self.max_api_version)?;
+        log::info!(
+            "Vulkan instance version: {}.{}",
+            vk::api_version_major(api_version),
+            vk::api_version_minor(api_version)
+        );
+
         let available_extensions = entry
             .enumerate_instance_extension_properties(None)?
             .into_iter()
             .map(ExtensionProperties::from)
             .collect::<Vec<_>>();
+        let extension_available = |name: &str| {
+            available_extensions
+                .iter()
+                .any(|e| e.extension_name == name)
+        };
+        let available_layers = entry
+            .enumerate_instance_layer_properties()?
+            .iter()
+            .map(|l| unsafe {
+                // SAFETY: According to the Vulkan Specification, `layer_name` is a
+                // null-terminated UTF-8 string.
+                String::from_utf8_unchecked(CStr::from_ptr(l.layer_name.as_ptr()).to_bytes().into())
+            })
+            .collect::<Vec<_>>();
+        let layer_available = |name: &str| available_layers.iter().any(|l| l == name);
 
-        let extensions_supported = required_extensions
+        let mut enabled_extensions = Vec::new();
+        for extension in &self.required_extensions {
+            push_unique(&mut enabled_extensions, extension);
+        }
+        if let Some(display) = self.display {
+            let Some(surface_extension) = surface_extension(display) else {
+                return Err(Error::UnsupportedWindow(format!("{:?}", display)));
+            };
+            log::info!("Surface extension enabled: {}", surface_extension);
+            push_unique(&mut enabled_extensions, "VK_KHR_surface");
+            push_unique(&mut enabled_extensions, surface_extension);
+        }
+        let missing_extensions = enabled_extensions
+            .iter()
+            .filter(|e| !extension_available(e))
+            .cloned()
+            .collect::<Vec<_>>();
+        let missing_layers = self
+            .required_layers
             .iter()
-            .all(|r| available_extensions.iter().any(|e| r == &e.extension_name));
+            .filter(|l| !layer_available(l))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing_extensions.is_empty() || !missing_layers.is_empty() {
+            return Err(Error::MissingInstanceRequirements {
+                extensions: missing_extensions,
+                layers: missing_layers,
+            });
+        }
+
+        for extension in &self.optional_extensions {
+            if extension_available(extension) {
+                log::info!("Optional instance extension {} enabled.", extension);
+                push_unique(&mut enabled_extensions, extension);
+            } else {
+                log::info!(
+                    "Optional instance extension {} is not supported.",
+                    extension
+                );
+            }
+        }
+        let mut enabled_layers = Vec::new();
+        for layer in &self.required_layers {
+            push_unique(&mut enabled_layers, layer);
+        }
+        for layer in &self.optional_layers {
+            if layer_available(layer) {
+                log::info!("Optional layer {} enabled.", layer);
+                push_unique(&mut enabled_layers, layer);
+            } else {
+                log::info!("Optional layer {} is not installed.", layer);
+            }
+        }
 
-        if !extensions_supported {
-            panic!("Required extensions are not supported.");
+        let validation = self.validation;
+        let debug_utils = validation.is_enabled() && extension_available("VK_EXT_debug_utils");
+        if debug_utils {
+            log::info!("Debug utilities extension enabled.");
+            push_unique(&mut enabled_extensions, "VK_EXT_debug_utils");
+        } else if validation.is_enabled() {
+            log::warn!("VK_EXT_debug_utils is not available, validation messages are not logged.");
+        }
+        if validation.is_enabled() {
+            if layer_available(VALIDATION_LAYER) {
+                log::info!("Validation layer enabled.");
+                push_unique(&mut enabled_layers, VALIDATION_LAYER);
+            } else {
+                log::warn!("{} is not available.", VALIDATION_LAYER);
+            }
+        }
+
+        let mut flags = vk::InstanceCreateFlags::empty();
+        if self.portability_enumeration {
+            if extension_available("VK_KHR_portability_enumeration") {
+                log::info!("Portability enumeration enabled.");
+                push_unique(&mut enabled_extensions, "VK_KHR_portability_enumeration");
+                flags |= vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR;
+                // Needed to query what a portability device does not support.
+                if api_version < vk::API_VERSION_1_1
+                    && extension_available("VK_KHR_get_physical_device_properties2")
+                {
+                    push_unique(
+                        &mut enabled_extensions,
+                        "VK_KHR_get_physical_device_properties2",
+                    );
+                }
+            } else {
+                log::warn!("VK_KHR_portability_enumeration is not available.");
+            }
         }
 
-        let required_extensions = required_extensions
+        let enabled_extensions = enabled_extensions
             .iter()
-            .map(|&e| unsafe {
-                // SAFETY: `required_extensions` does not contain nul.
-                CString::from_vec_unchecked(e.into())
+            .map(|e| unsafe {
+                // SAFETY: Extension names do not contain nul.
+                CString::from_vec_unchecked(e.clone().into())
             })
             .collect::<Vec<_>>();
-        let pp_enabled_extension_names = required_extensions
+        let pp_enabled_extension_names = enabled_extensions
             .iter()
             .map(|e| e.as_ptr())
             .collect::<Vec<_>>();
+        let enabled_layers = enabled_layers
+            .iter()
+            .map(|l| unsafe {
+                // SAFETY: Layer names do not contain nul.
+                CString::from_vec_unchecked(l.clone().into())
+            })
+            .collect::<Vec<_>>();
+        let pp_enabled_layer_names = enabled_layers
+            .iter()
+            .map(|l| l.as_ptr())
+            .collect::<Vec<_>>();
 
+        let app_name =
+            CString::new(self.app_name.replace('\0', "")).expect("Nul bytes were removed");
         let app_info = vk::ApplicationInfo {
-            p_application_name: unsafe {
-                CStr::from_bytes_with_nul_unchecked(b"Vulkan Tutorial\0").as_ptr()
-            },
-            application_version: vk::make_api_version(0, 0, 1, 0),
-            p_engine_name: unsafe {
-                CStr::from_bytes_with_nul_unchecked(b"Tanelorn Engine\0").as_ptr()
-            },
+            p_application_name: app_name.as_ptr(),
+            application_version: self.app_version,
+            p_engine_name: c"Tanelorn Engine".as_ptr(),
             engine_version: vk::make_api_version(0, 0, 1, 0),
-            api_version: vk::API_VERSION_1_0,
+            api_version,
             ..Default::default()
         };
+        let messenger_state = MessengerState::new(validation);
+        // Chained so that instance creation and destruction are reported as well.
+        let messenger_create_info = messenger_state.create_info();
         let create_info = vk::InstanceCreateInfo {
+            p_next: if debug_utils {
+                &messenger_create_info as *const _ as *const c_void
+            } else {
+                std::ptr::null()
+            },
+            flags,
             p_application_info: &app_info,
+            pp_enabled_layer_names: pp_enabled_layer_names.as_ptr(),
+            enabled_layer_count: pp_enabled_layer_names
+                .len()
+                .try_into()
+                .expect("Could not convert `usize` to `u32`"),
             pp_enabled_extension_names: pp_enabled_extension_names.as_ptr(),
             enabled_extension_count: pp_enabled_extension_names
                 .len()
@@ -111,55 +508,217 @@ impl Instance {
             ..Default::default()
         };
         let instance = unsafe { entry.create_instance(&create_info, None)? };
+        log::trace!("Instance created.");
 
+        let debug_messenger = if debug_utils {
+            let loader = |name: &CStr| unsafe {
+                entry
+                    .get_instance_proc_addr(instance.handle(), name.as_ptr())
+                    .map(|f| NonNull::new_unchecked(f as *mut ()))
+            };
+            // SAFETY: `VK_EXT_debug_utils` is enabled on `instance`.
+            match unsafe { DebugMessenger::new(instance.handle(), loader, messenger_state) } {
+                Ok(messenger) => Some(messenger),
+                Err((e, messenger_state)) => {
+                    // The chained messenger reports through the state until the
+                    // instance is gone, so it is freed only afterwards.
+                    unsafe { instance.destroy_instance(None) };
+                    drop(messenger_state);
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
 
-        log::trace!("Instance created.");
-        Ok(Self {
+        Ok(Instance {
             inner: Arc::new(RawInstance {
                 _lib: lib,
+                entry,
                 handle: instance,
+                api_version: app_info.api_version,
+                debug_messenger,
             }),
         })
     }
+}
 
-    pub fn enumerate_physical_devices(&self) -> impl ExactSizeIterator<Item = PhysicalDevice> {
+impl Instance {
+    pub fn enumerate_physical_devices(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = PhysicalDevice>> {
         // TODO: Document `SAFETY`
         unsafe {
             let physical_devices = self
                 .inner
                 .handle
-                .enumerate_physical_devices()
-                .unwrap()
+                .enumerate_physical_devices()?
                 .into_iter()
-                .map(move |p| {
-                    let props = self.inner.handle.get_physical_device_properties(p);
-                    let device_name = String::from_utf8_unchecked(
-                        CStr::from_ptr(props.device_name.as_ptr()).to_bytes().into(),
-                    );
-
-                    PhysicalDevice {
-                        handle: p,
-                        instance: self.clone(),
-                        props: PhysicalDeviceProperties {
-                            device_type: props.device_type.into(),
-                            device_name,
-                        },
-                    }
-                })
+                .map(move |p| self.physical_device(p))
                 .collect::<Vec<PhysicalDevice>>();
 
-            physical_devices.into_iter()
+            Ok(physical_devices.into_iter())
+        }
+    }
+
+    /// Queries everything [`PhysicalDevice`] exposes up front.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must have been enumerated from this instance.
+    unsafe fn physical_device(&self, handle: vk::PhysicalDevice) -> PhysicalDevice {
+        let instance = &self.inner.handle;
+        let props = instance.get_physical_device_properties(handle);
+        let memory_properties = instance.get_physical_device_memory_properties(handle);
+        let features = instance.get_physical_device_features(handle);
+
+        // The structs of the feature chain only exist since Vulkan 1.2 and
+        // `vkGetPhysicalDeviceFeatures2` needs the instance to be at least 1.1.
+        let api_version = props.api_version.min(self.inner.api_version);
+        let mut vulkan_11_features = vk::PhysicalDeviceVulkan11Features::default();
+        let mut vulkan_12_features = vk::PhysicalDeviceVulkan12Features::default();
+        let mut vulkan_13_features = vk::PhysicalDeviceVulkan13Features::default();
+        if api_version >= vk::API_VERSION_1_2 {
+            if api_version >= vk::API_VERSION_1_3 {
+                vulkan_12_features.p_next = &mut vulkan_13_features as *mut _ as *mut c_void;
+            }
+            vulkan_11_features.p_next = &mut vulkan_12_features as *mut _ as *mut c_void;
+            let mut features2 = vk::PhysicalDeviceFeatures2 {
+                p_next: &mut vulkan_11_features as *mut _ as *mut c_void,
+                ..Default::default()
+            };
+            instance.get_physical_device_features2(handle, &mut features2);
+        }
+
+        PhysicalDevice {
+            handle,
+            instance: self.clone(),
+            props: props.into(),
+            features: features.into(),
+            vulkan_11_features: (api_version >= vk::API_VERSION_1_2)
+                .then(|| vulkan_11_features.into()),
+            vulkan_12_features: (api_version >= vk::API_VERSION_1_2)
+                .then(|| vulkan_12_features.into()),
+            vulkan_13_features: (api_version >= vk::API_VERSION_1_3)
+                .then(|| vulkan_13_features.into()),
+            memory_properties: memory_properties.into(),
+        }
+    }
+
+    /// The Vulkan version the instance was created for.
+    pub fn api_version(&self) -> u32 {
+        self.inner.api_version
+    }
+
+    /// Whether `VK_EXT_debug_utils` is enabled, which is the case whenever
+    /// validation is.
+    pub fn debug_utils_enabled(&self) -> bool {
+        self.inner.debug_messenger.is_some()
+    }
+
+    /// Validation errors reported since the last call. Always empty unless the
+    /// instance was created with [`Validation::PanicOnError`].
+    pub fn take_validation_errors(&self) -> Vec<String> {
+        self.inner
+            .debug_messenger
+            .as_ref()
+            .map_or_else(Vec::new, DebugMessenger::take_errors)
+    }
+
+    fn proc_addr(&self, name: &CStr) -> Option<NonNull<()>> {
+        unsafe {
+            self.inner
+                .entry
+                .get_instance_proc_addr(self.inner.handle.handle(), name.as_ptr())
+                .map(|f| NonNull::new_unchecked(f as *mut ()))
+        }
+    }
+}
+
+/// Pushes `name` unless it is already in `names`.
+/// The version to create an instance for: the loader's, clamped to `max` and
+/// without its patch number. Fails if the loader is older than `min`.
+fn negotiate_api_version(loader_version: u32, min: u32, max: u32) -> Result<u32> {
+    if loader_version < min {
+        return Err(Error::UnsupportedApiVersion {
+            required: min,
+            supported: loader_version,
+        });
+    }
+
+    let api_version = loader_version.min(max);
+    Ok(vk::make_api_version(
+        0,
+        vk::api_version_major(api_version),
+        vk::api_version_minor(api_version),
+        0,
+    ))
+}
+
+fn push_unique(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|n| n == name) {
+        names.push(name.to_owned());
+    }
+}
+
+/// Names in `required` that are not in `available`.
+fn missing_extensions(required: &[&str], available: &[ExtensionProperties]) -> Vec<String> {
+    required
+        .iter()
+        .filter(|&&r| !available.iter().any(|e| e.extension_name == r))
+        .map(|&r| r.to_owned())
+        .collect()
+}
+
+/// The platform surface extension needed to present to windows of `display`.
+fn surface_extension(display: RawDisplayHandle) -> Option<&'static str> {
+    match display {
+        RawDisplayHandle::Windows(_) => Some("VK_KHR_win32_surface"),
+        RawDisplayHandle::Xlib(_) => Some("VK_KHR_xlib_surface"),
+        RawDisplayHandle::Xcb(_) => Some("VK_KHR_xcb_surface"),
+        RawDisplayHandle::Wayland(_) => Some("VK_KHR_wayland_surface"),
+        _ => None,
+    }
+}
+
+/// Prefers a Vulkan loader that is already in the process, either preloaded
+/// (e.g. by a capture tool) or linked statically, before loading a fresh copy.
+fn load_vulkan_library(candidates: &[&str]) -> dynamic_library::Result<DynamicLibrary> {
+    for name in candidates {
+        if let Ok(lib) = DynamicLibrary::open_existing(name) {
+            log::info!("Using already loaded Vulkan loader `{}`.", name);
+            return Ok(lib);
         }
     }
+
+    if let Ok(lib) = DynamicLibrary::this_process() {
+        if lib.get("vkGetInstanceProcAddr").is_ok() {
+            log::info!("Using Vulkan loader linked into the process.");
+            return Ok(lib);
+        }
+    }
+
+    // An explicitly configured loader takes precedence over the system one.
+    Search::new()
+        .env_var("TANELORN_VULKAN_LOADER")
+        .candidates(candidates)
+        .open()
 }
 
 struct RawInstance {
     _lib: DynamicLibrary,
+    entry: ash::Entry,
     handle: ash::Instance,
+    api_version: u32,
+    debug_messenger: Option<DebugMessenger>,
 }
 
 impl Drop for RawInstance {
     fn drop(&mut self) {
+        if let Some(messenger) = self.debug_messenger.as_mut() {
+            // SAFETY: The messenger was created for this instance.
+            unsafe { messenger.destroy(self.handle.handle()) };
+        }
         log::trace!("Instance destroyed.");
         unsafe { self.handle.destroy_instance(None) }
     }
@@ -169,6 +728,11 @@ pub struct PhysicalDevice {
     handle: vk::PhysicalDevice,
     instance: Instance,
     props: PhysicalDeviceProperties,
+    features: PhysicalDeviceFeatures,
+    vulkan_11_features: Option<PhysicalDeviceVulkan11Features>,
+    vulkan_12_features: Option<PhysicalDeviceVulkan12Features>,
+    vulkan_13_features: Option<PhysicalDeviceVulkan13Features>,
+    memory_properties: PhysicalDeviceMemoryProperties,
 }
 
 impl PhysicalDevice {
@@ -183,7 +747,70 @@ impl PhysicalDevice {
         props.into_iter().map(|p| p.into()).collect()
     }
 
-    pub fn surface_support_khr(&self, queue_family_index: usize, surface: &SurfaceKhr) -> bool {
+    /// Picks a queue family for each [`QueueRole`], preferring families that
+    /// let compute and transfer work run alongside graphics. There is a
+    /// present family only if `surface` is given.
+    pub fn queue_families(&self, surface: Option<&SurfaceKhr>) -> Result<QueueFamilies> {
+        let families = self.queue_family_properties();
+        let find = |f: &dyn Fn(&QueueFamilyProperties) -> bool| {
+            families.iter().position(|p| p.queue_count > 0 && f(p))
+        };
+
+        // A graphics family that can also present saves sharing swapchain
+        // images between families.
+        let mut graphics = None;
+        let mut present = None;
+        for (i, props) in families.iter().enumerate() {
+            if props.queue_count == 0 || !props.graphics() {
+                continue;
+            }
+            if let Some(surface) = surface {
+                if self.surface_support_khr(i, surface)? {
+                    graphics = Some(i);
+                    present = Some(i);
+                    break;
+                }
+            }
+            graphics.get_or_insert(i);
+        }
+        let graphics = graphics.ok_or(Error::MissingQueueFamily("graphics"))?;
+
+        if let Some(surface) = surface {
+            if present.is_none() {
+                for (i, props) in families.iter().enumerate() {
+                    if props.queue_count > 0 && self.surface_support_khr(i, surface)? {
+                        present = Some(i);
+                        break;
+                    }
+                }
+            }
+            if present.is_none() {
+                return Err(Error::MissingQueueFamily("presentation"));
+            }
+        }
+
+        let compute = find(&|p| p.compute() && !p.graphics())
+            .or_else(|| families[graphics].compute().then_some(graphics))
+            .or_else(|| find(&|p| p.compute()))
+            .ok_or(Error::MissingQueueFamily("compute"))?;
+        // Graphics and compute families support transfers without saying so.
+        let transfer = find(&|p| p.transfer() && !p.graphics() && !p.compute())
+            .or_else(|| find(&|p| p.transfer() && !p.graphics()))
+            .unwrap_or(graphics);
+
+        Ok(QueueFamilies {
+            graphics,
+            present,
+            compute,
+            transfer,
+        })
+    }
+
+    pub fn surface_support_khr(
+        &self,
+        queue_family_index: usize,
+        surface: &SurfaceKhr,
+    ) -> Result<bool> {
         let mut support = MaybeUninit::uninit();
         let res = unsafe {
             (surface.surface_fn.get_physical_device_surface_support_khr)(
@@ -196,49 +823,241 @@ impl PhysicalDevice {
             )
         };
 
-        if res == vk::Result::SUCCESS {
-            unsafe { support.assume_init() != 0 }
-        } else {
-            panic!("Failed to get physical device surface support: {:?}", res)
+        match res {
+            vk::Result::SUCCESS => Ok(unsafe { support.assume_init() } != 0),
+            code => Err(code.into()),
+        }
+    }
+
+    pub fn surface_capabilities(&self, surface: &SurfaceKhr) -> Result<SurfaceCapabilitiesKhr> {
+        let mut capabilities = MaybeUninit::uninit();
+        let res = unsafe {
+            (surface
+                .surface_fn
+                .get_physical_device_surface_capabilities_khr)(
+                self.handle,
+                surface.handle,
+                capabilities.as_mut_ptr(),
+            )
+        };
+
+        match res {
+            vk::Result::SUCCESS => Ok(unsafe { capabilities.assume_init() }.into()),
+            code => Err(code.into()),
         }
     }
 
-    pub fn surface_capabilities(&self, surface: &SurfaceKhr) -> () {}
+    pub fn surface_formats(&self, surface: &SurfaceKhr) -> Result<Vec<SurfaceFormat>> {
+        // SAFETY: The command writes at most `count` elements to the pointer.
+        let formats = unsafe {
+            enumerate(|count, data| {
+                (surface.surface_fn.get_physical_device_surface_formats_khr)(
+                    self.handle,
+                    surface.handle,
+                    count,
+                    data,
+                )
+            })?
+        };
+
+        Ok(formats.into_iter().map(SurfaceFormat::from).collect())
+    }
+
+    pub fn surface_present_modes(&self, surface: &SurfaceKhr) -> Result<Vec<PresentMode>> {
+        // SAFETY: The command writes at most `count` elements to the pointer.
+        let present_modes = unsafe {
+            enumerate(|count, data| {
+                (surface
+                    .surface_fn
+                    .get_physical_device_surface_present_modes_khr)(
+                    self.handle,
+                    surface.handle,
+                    count,
+                    data,
+                )
+            })?
+        };
+
+        Ok(present_modes.into_iter().map(PresentMode::from).collect())
+    }
+
+    pub fn properties(&self) -> &PhysicalDeviceProperties {
+        &self.props
+    }
 
     pub fn device_name(&self) -> &str {
-        &self.props.device_name
+        self.props.device_name()
     }
 
     pub fn device_type(&self) -> PhysicalDeviceType {
-        self.props.device_type
+        self.props.device_type()
+    }
+
+    /// Highest Vulkan version supported by the device, as made by
+    /// `vk::make_api_version`.
+    pub fn api_version(&self) -> u32 {
+        self.props.api_version()
+    }
+
+    pub fn features(&self) -> &PhysicalDeviceFeatures {
+        &self.features
+    }
+
+    /// `None` unless both the device and the instance support Vulkan 1.2.
+    pub fn vulkan_11_features(&self) -> Option<&PhysicalDeviceVulkan11Features> {
+        self.vulkan_11_features.as_ref()
+    }
+
+    /// `None` unless both the device and the instance support Vulkan 1.2.
+    pub fn vulkan_12_features(&self) -> Option<&PhysicalDeviceVulkan12Features> {
+        self.vulkan_12_features.as_ref()
+    }
+
+    /// `None` unless both the device and the instance support Vulkan 1.3.
+    pub fn vulkan_13_features(&self) -> Option<&PhysicalDeviceVulkan13Features> {
+        self.vulkan_13_features.as_ref()
+    }
+
+    /// Every supported feature, with those of unsupported Vulkan versions
+    /// left disabled.
+    pub fn supported_features(&self) -> DeviceFeatures {
+        DeviceFeatures {
+            core: self.features,
+            vulkan_11: self.vulkan_11_features.unwrap_or_default(),
+            vulkan_12: self.vulkan_12_features.unwrap_or_default(),
+            vulkan_13: self.vulkan_13_features.unwrap_or_default(),
+        }
+    }
+
+    pub fn memory_properties(&self) -> &PhysicalDeviceMemoryProperties {
+        &self.memory_properties
+    }
+
+    /// Fails with [`Error::MissingDeviceRequirements`] listing everything in
+    /// `extensions` and `features` that the device does not support.
+    pub fn check_support(&self, extensions: &[&str], features: &DeviceFeatures) -> Result<()> {
+        let extensions = missing_extensions(extensions, &self.extension_properties()?);
+        let features = features.missing(&self.supported_features());
+        if extensions.is_empty() && features.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MissingDeviceRequirements {
+                extensions,
+                features,
+            })
+        }
+    }
+
+    pub fn extension_properties(&self) -> Result<Vec<ExtensionProperties>> {
+        let props = unsafe {
+            self.instance
+                .inner
+                .handle
+                .enumerate_device_extension_properties(self.handle)?
+        };
+
+        Ok(props.into_iter().map(ExtensionProperties::from).collect())
+    }
+
+    /// Total size of the memory heaps local to the device.
+    pub fn device_local_memory_size(&self) -> u64 {
+        self.memory_properties
+            .memory_heaps()
+            .iter()
+            .filter(|heap| heap.device_local())
+            .map(|heap| heap.size())
+            .sum()
+    }
+}
+
+/// Calls a Vulkan enumeration command twice, first for the count and then for
+/// the elements, retrying while it reports `INCOMPLETE`.
+///
+/// # Safety
+///
+/// `f` must write at most `count` elements to its pointer argument.
+unsafe fn enumerate<T: Default + Clone>(
+    mut f: impl FnMut(&mut u32, *mut T) -> vk::Result,
+) -> Result<Vec<T>> {
+    loop {
+        let mut count = 0;
+        match f(&mut count, std::ptr::null_mut()) {
+            vk::Result::SUCCESS => (),
+            code => return Err(code.into()),
+        }
+
+        let mut data = vec![T::default(); count as usize];
+        match f(&mut count, data.as_mut_ptr()) {
+            vk::Result::SUCCESS => {
+                data.truncate(count as usize);
+                return Ok(data);
+            }
+            vk::Result::INCOMPLETE => continue,
+            code => return Err(code.into()),
+        }
     }
 }
 
-pub struct PhysicalDeviceProperties {
-    device_type: PhysicalDeviceType,
-    device_name: String,
+/// What a queue is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueRole {
+    Graphics,
+    Present,
+    Compute,
+    Transfer,
 }
 
+/// The queue family of each [`QueueRole`], as picked by
+/// [`PhysicalDevice::queue_families`]. Roles share a family when the device
+/// has no separate one for them.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PhysicalDeviceType {
-    Other,
-    IntegratedGpu,
-    DiscreteGpu,
-    VirtualGpu,
-    Cpu,
+pub struct QueueFamilies {
+    graphics: usize,
+    present: Option<usize>,
+    compute: usize,
+    transfer: usize,
 }
 
-impl From<vk::PhysicalDeviceType> for PhysicalDeviceType {
-    fn from(t: vk::PhysicalDeviceType) -> Self {
-        match t {
-            vk::PhysicalDeviceType::OTHER => Self::Other,
-            vk::PhysicalDeviceType::INTEGRATED_GPU => Self::IntegratedGpu,
-            vk::PhysicalDeviceType::DISCRETE_GPU => Self::DiscreteGpu,
-            vk::PhysicalDeviceType::VIRTUAL_GPU => Self::VirtualGpu,
-            vk::PhysicalDeviceType::CPU => Self::Cpu,
-            _ => unsafe { std::hint::unreachable_unchecked() },
+impl QueueFamilies {
+    /// `None` only for [`QueueRole::Present`] when there is no surface.
+    pub fn get(&self, role: QueueRole) -> Option<usize> {
+        match role {
+            QueueRole::Graphics => Some(self.graphics),
+            QueueRole::Present => self.present,
+            QueueRole::Compute => Some(self.compute),
+            QueueRole::Transfer => Some(self.transfer),
         }
     }
+
+    pub fn graphics(&self) -> usize {
+        self.graphics
+    }
+
+    pub fn present(&self) -> Option<usize> {
+        self.present
+    }
+
+    pub fn compute(&self) -> usize {
+        self.compute
+    }
+
+    pub fn transfer(&self) -> usize {
+        self.transfer
+    }
+
+    /// Every family in use, each once.
+    pub fn unique(&self) -> Vec<usize> {
+        let mut families = vec![self.graphics];
+        for family in [self.present, Some(self.compute), Some(self.transfer)]
+            .into_iter()
+            .flatten()
+        {
+            if !families.contains(&family) {
+                families.push(family);
+            }
+        }
+        families
+    }
 }
 
 #[derive(Debug)]
@@ -311,6 +1130,38 @@ pub struct QueueFamilyCapabilities {
     protected: bool,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Extent2D {
+    width: u32,
+    height: u32,
+}
+
+impl Extent2D {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl From<vk::Extent2D> for Extent2D {
+    fn from(vk::Extent2D { width, height }: vk::Extent2D) -> Self {
+        Self { width, height }
+    }
+}
+
+impl From<Extent2D> for vk::Extent2D {
+    fn from(Extent2D { width, height }: Extent2D) -> Self {
+        Self { width, height }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Extent3D {
     width: u32,
@@ -340,101 +1191,327 @@ pub struct Device {
     instance: Instance,
 }
 
-impl Device {
-    pub fn new(physical_device: &PhysicalDevice, queue_family_index: usize) -> Self {
-        let queue_priority = 1.0f32;
-        let queue_create_info = vk::DeviceQueueCreateInfo {
-            queue_family_index: queue_family_index
-                .try_into()
-                .expect("Could not convert `usize` to `u32`"),
-            queue_count: 1,
-            p_queue_priorities: &queue_priority,
-            ..Default::default()
-        };
+/// Creates a [`Device`] with the extensions and features it is asked for.
+/// Required ones must all be supported, optional ones are enabled only where
+/// they are.
+pub struct DeviceBuilder<'a> {
+    physical_device: &'a PhysicalDevice,
+    queue_families: QueueFamilies,
+    required_extensions: Vec<String>,
+    optional_extensions: Vec<String>,
+    required_features: DeviceFeatures,
+    optional_features: DeviceFeatures,
+}
 
-        let required_extensions = ["VK_KHR_swapchain"];
-        let available_extensions = unsafe {
-            physical_device
-                .instance
-                .inner
-                .handle
-                .enumerate_device_extension_properties(physical_device.handle)
-                .unwrap()
-                .into_iter()
-                .map(ExtensionProperties::from)
-                .collect::<Vec<_>>()
-        };
-        let supported = required_extensions.iter().all(|required| {
-            available_extensions
+impl<'a> DeviceBuilder<'a> {
+    /// The device gets one queue from each of `queue_families`.
+    pub fn new(physical_device: &'a PhysicalDevice, queue_families: QueueFamilies) -> Self {
+        Self {
+            physical_device,
+            queue_families,
+            required_extensions: Vec::new(),
+            optional_extensions: Vec::new(),
+            required_features: DeviceFeatures::default(),
+            optional_features: DeviceFeatures::default(),
+        }
+    }
+
+    pub fn required_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn optional_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.optional_extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn required_features(mut self, features: DeviceFeatures) -> Self {
+        self.required_features = self.required_features.union(&features);
+        self
+    }
+
+    pub fn optional_features(mut self, features: DeviceFeatures) -> Self {
+        self.optional_features = self.optional_features.union(&features);
+        self
+    }
+
+    pub fn build(&self) -> Result<Device> {
+        let physical_device = self.physical_device;
+        let required_extensions = self
+            .required_extensions
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        physical_device.check_support(&required_extensions, &self.required_features)?;
+
+        let available_extensions = physical_device.extension_properties()?;
+        // The renderer and the application may both require an extension.
+        let mut enabled_extensions = Vec::new();
+        for extension in &required_extensions {
+            push_unique(&mut enabled_extensions, extension);
+        }
+        // Must be enabled on devices that only partially conform.
+        if available_extensions
+            .iter()
+            .any(|e| e.extension_name == "VK_KHR_portability_subset")
+        {
+            push_unique(&mut enabled_extensions, "VK_KHR_portability_subset");
+        }
+        for extension in &self.optional_extensions {
+            if enabled_extensions.contains(extension) {
+                continue;
+            }
+            if available_extensions
                 .iter()
-                .any(|available| required == &available.extension_name)
-        });
-        if !supported {
-            panic!("Required device extension(s) are not supported.")
+                .any(|e| e.extension_name == *extension)
+            {
+                log::info!("Optional device extension {} enabled.", extension);
+                enabled_extensions.push(extension.clone());
+            } else {
+                log::info!("Optional device extension {} is not supported.", extension);
+            }
+        }
+
+        let supported_features = physical_device.supported_features();
+        let optional_features = self.optional_features.intersection(&supported_features);
+        for feature in optional_features.names() {
+            log::info!("Optional device feature {} enabled.", feature);
+        }
+        for feature in self.optional_features.missing(&supported_features) {
+            log::info!("Optional device feature {} is not supported.", feature);
         }
+        let enabled_features = self.required_features.union(&optional_features);
 
-        let enabled_extensions = required_extensions
+        let queue_priority = 1.0f32;
+        let queue_create_infos = self
+            .queue_families
+            .unique()
+            .into_iter()
+            .map(|family| vk::DeviceQueueCreateInfo {
+                queue_family_index: family
+                    .try_into()
+                    .expect("Could not convert `usize` to `u32`"),
+                queue_count: 1,
+                p_queue_priorities: &queue_priority,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let extension_names = enabled_extensions
             .iter()
-            .map(|&e| unsafe {
-                // SAFETY: `required_extensions` does not contain nul.
-                CString::from_vec_unchecked(e.into())
+            .map(|e| unsafe {
+                // SAFETY: Extension names do not contain nul.
+                CString::from_vec_unchecked(e.clone().into())
             })
             .collect::<Vec<_>>();
-        let enabled_extension_pointers = enabled_extensions
+        let enabled_extension_pointers = extension_names
             .iter()
             .map(|e| e.as_ptr())
             .collect::<Vec<_>>();
 
+        // Features of later versions can only be enabled through a chain, which
+        // must not contain structs the device does not know.
+        let mut vulkan_11_features =
+            vk::PhysicalDeviceVulkan11Features::from(enabled_features.vulkan_11);
+        let mut vulkan_12_features =
+            vk::PhysicalDeviceVulkan12Features::from(enabled_features.vulkan_12);
+        let mut vulkan_13_features =
+            vk::PhysicalDeviceVulkan13Features::from(enabled_features.vulkan_13);
+        if physical_device.vulkan_13_features.is_some() {
+            vulkan_12_features.p_next = &mut vulkan_13_features as *mut _ as *mut c_void;
+        }
+        vulkan_11_features.p_next = &mut vulkan_12_features as *mut _ as *mut c_void;
+        let core_features = vk::PhysicalDeviceFeatures::from(enabled_features.core);
+        let features2 = vk::PhysicalDeviceFeatures2 {
+            p_next: &mut vulkan_11_features as *mut _ as *mut c_void,
+            features: core_features,
+            ..Default::default()
+        };
+        let chained = physical_device.vulkan_11_features.is_some();
+
         let create_info = vk::DeviceCreateInfo {
-            queue_create_info_count: 1,
-            p_queue_create_infos: &queue_create_info,
+            p_next: if chained {
+                &features2 as *const _ as *const c_void
+            } else {
+                std::ptr::null()
+            },
+            queue_create_info_count: queue_create_infos
+                .len()
+                .try_into()
+                .expect("Could not convert `usize` to `u32`"),
+            p_queue_create_infos: queue_create_infos.as_ptr(),
             enabled_extension_count: enabled_extension_pointers
                 .len()
                 .try_into()
                 .expect("Failed to convert `usize` to `u32`"),
             pp_enabled_extension_names: enabled_extension_pointers.as_ptr(),
+            p_enabled_features: if chained {
+                std::ptr::null()
+            } else {
+                &core_features
+            },
             ..Default::default()
         };
 
         let handle = unsafe {
+            physical_device.instance.inner.handle.create_device(
+                physical_device.handle,
+                &create_info,
+                None,
+            )?
+        };
+
+        let memory_properties = unsafe {
             physical_device
                 .instance
                 .inner
                 .handle
-                .create_device(physical_device.handle, &create_info, None)
-                .unwrap()
+                .get_physical_device_memory_properties(physical_device.handle)
+        };
+
+        let instance = &physical_device.instance;
+        let debug_utils = if instance.debug_utils_enabled() {
+            // SAFETY: The table is declared with the `PFN_*` types of its commands.
+            match unsafe { DebugUtilsDeviceFn::load_with(|name| instance.proc_addr(name)) } {
+                Ok(fp) => Some(fp),
+                Err(e) => {
+                    log::warn!("{}", e);
+                    None
+                }
+            }
+        } else {
+            None
         };
 
+        let queues = self
+            .queue_families
+            .unique()
+            .into_iter()
+            .map(|family| {
+                let queue = unsafe {
+                    handle.get_device_queue(
+                        family
+                            .try_into()
+                            .expect("Could not convert `usize` to `u32`"),
+                        0,
+                    )
+                };
+                debug_assert_ne!(queue, vk::Queue::null());
+                (family, Mutex::new(queue))
+            })
+            .collect();
+
         log::trace!("Device created.");
-        Self {
-            inner: Arc::new(RawDevice { handle }),
-            instance: physical_device.instance.clone(),
+        Ok(Device {
+            inner: Arc::new(RawDevice {
+                handle,
+                memory_properties,
+                debug_utils,
+                queue_families: self.queue_families,
+                queues,
+                enabled_extensions,
+                enabled_features,
+            }),
+            instance: instance.clone(),
+        })
+    }
+}
+
+impl Device {
+    pub fn queue_families(&self) -> QueueFamilies {
+        self.inner.queue_families
+    }
+
+    /// The queue created for `role`. Roles that share a family share a queue,
+    /// and work submitted to it through any of them is serialised.
+    pub fn queue(&self, role: QueueRole) -> Option<Queue> {
+        self.inner.queue_families.get(role).map(|family| Queue {
+            family_index: family,
+            device: self.clone(),
+        })
+    }
+
+    pub fn enabled_extensions(&self) -> &[String] {
+        &self.inner.enabled_extensions
+    }
+
+    pub fn is_extension_enabled(&self, extension: &str) -> bool {
+        self.inner.enabled_extensions.iter().any(|e| e == extension)
+    }
+
+    /// The required features and the optional ones that are supported.
+    pub fn enabled_features(&self) -> &DeviceFeatures {
+        &self.inner.enabled_features
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.set_object_name(self.inner.handle.handle(), name)
+    }
+
+    /// Does nothing unless `VK_EXT_debug_utils` is enabled.
+    fn set_object_name<H: vk::Handle>(&self, handle: H, name: &str) -> Result<()> {
+        match &self.inner.debug_utils {
+            // SAFETY: Every wrapper passes a handle it owns, created from this device.
+            Some(fp) => unsafe { fp.set_object_name(self.inner.handle.handle(), handle, name) },
+            None => Ok(()),
         }
     }
 
-    pub fn get_queue(&self, queue_family_index: usize, queue_index: usize) -> Queue {
-        let handle = unsafe {
-            self.inner.handle.get_device_queue(
-                queue_family_index
-                    .try_into()
-                    .expect("Could not convert `usize` to `u32`"),
-                queue_index
-                    .try_into()
-                    .expect("Could not convert `usize` to `u32`"),
-            )
-        };
+    pub fn wait_idle(&self) -> Result<()> {
+        // Every queue of the device must be externally synchronized.
+        let _queues = self
+            .inner
+            .queues
+            .iter()
+            .map(|(_, queue)| queue.lock().unwrap_or_else(PoisonError::into_inner))
+            .collect::<Vec<_>>();
+        unsafe { self.inner.handle.device_wait_idle()? };
+        Ok(())
+    }
 
-        debug_assert_ne!(handle, vk::Queue::null());
+    /// Index of the first memory type allowed by `type_bits` that has all of `flags`.
+    fn find_memory_type(&self, type_bits: u32, flags: vk::MemoryPropertyFlags) -> Option<u32> {
+        let props = &self.inner.memory_properties;
+        (0..props.memory_type_count).find(|&i| {
+            type_bits & (1 << i) != 0
+                && props.memory_types[i as usize]
+                    .property_flags
+                    .contains(flags)
+        })
+    }
 
-        Queue {
-            handle,
-            device: self.clone(),
+    fn proc_addr(&self, name: &CStr) -> Option<NonNull<()>> {
+        unsafe {
+            self.instance
+                .inner
+                .handle
+                .get_device_proc_addr(self.inner.handle.handle(), name.as_ptr())
+                .map(|f| NonNull::new_unchecked(f as *mut ()))
         }
     }
 }
 
 struct RawDevice {
     handle: ash::Device,
+    memory_properties: vk::PhysicalDeviceMemoryProperties,
+    debug_utils: Option<DebugUtilsDeviceFn>,
+    queue_families: QueueFamilies,
+    /// The queue of each family in use. Submitting and presenting require
+    /// external synchronization, so they lock the queue.
+    queues: Vec<(usize, Mutex<vk::Queue>)>,
+    enabled_extensions: Vec<String>,
+    enabled_features: DeviceFeatures,
 }
 
 impl Drop for RawDevice {
@@ -444,75 +1521,240 @@ impl Drop for RawDevice {
     }
 }
 
+/// A queue of a [`Device`]. Handles to the same queue can be used from several
+/// threads, since every use of the queue locks it.
+#[derive(Clone)]
 pub struct Queue {
-    handle: vk::Queue,
+    family_index: usize,
     device: Device,
 }
 
+impl Queue {
+    pub fn family_index(&self) -> usize {
+        self.family_index
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        let handle = self.lock();
+        self.device.set_object_name(*handle, name)
+    }
+
+    /// The queue, locked until the guard is dropped.
+    fn lock(&self) -> MutexGuard<'_, vk::Queue> {
+        let (_, queue) = self
+            .device
+            .inner
+            .queues
+            .iter()
+            .find(|&&(family, _)| family == self.family_index)
+            .expect("Device was created with a queue for every family in use");
+        // The handle cannot be left inconsistent by a panicking thread.
+        queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Submits `command_buffer` once every semaphore in `wait` is signaled,
+    /// each blocking the given stages. `signal` and `fence` are signaled once it
+    /// has completed.
+    pub fn submit(
+        &self,
+        command_buffer: &CommandBuffer,
+        wait: &[(&Semaphore, vk::PipelineStageFlags)],
+        signal: &[&Semaphore],
+        fence: Option<&Fence>,
+    ) -> Result<()> {
+        let wait_semaphores = wait.iter().map(|(s, _)| s.handle).collect::<Vec<_>>();
+        let wait_stages = wait.iter().map(|&(_, stage)| stage).collect::<Vec<_>>();
+        let signal_semaphores = signal.iter().map(|s| s.handle).collect::<Vec<_>>();
+
+        let submit_info = vk::SubmitInfo {
+            wait_semaphore_count: wait_semaphores
+                .len()
+                .try_into()
+                .expect("Could not convert `usize` to `u32`"),
+            p_wait_semaphores: wait_semaphores.as_ptr(),
+            p_wait_dst_stage_mask: wait_stages.as_ptr(),
+            command_buffer_count: 1,
+            p_command_buffers: &command_buffer.handle,
+            signal_semaphore_count: signal_semaphores
+                .len()
+                .try_into()
+                .expect("Could not convert `usize` to `u32`"),
+            p_signal_semaphores: signal_semaphores.as_ptr(),
+            ..Default::default()
+        };
+
+        let handle = self.lock();
+        unsafe {
+            self.device.inner.handle.queue_submit(
+                *handle,
+                &[submit_info],
+                fence.map_or(vk::Fence::null(), |f| f.handle),
+            )?
+        };
+        Ok(())
+    }
+}
+
+dynamic_library::api! {
+    struct SurfaceFn {
+        destroy_surface_khr: vk::PFN_vkDestroySurfaceKHR = "vkDestroySurfaceKHR",
+        get_physical_device_surface_support_khr: vk::PFN_vkGetPhysicalDeviceSurfaceSupportKHR =
+            "vkGetPhysicalDeviceSurfaceSupportKHR",
+        get_physical_device_surface_capabilities_khr:
+            vk::PFN_vkGetPhysicalDeviceSurfaceCapabilitiesKHR =
+            "vkGetPhysicalDeviceSurfaceCapabilitiesKHR",
+        get_physical_device_surface_formats_khr: vk::PFN_vkGetPhysicalDeviceSurfaceFormatsKHR =
+            "vkGetPhysicalDeviceSurfaceFormatsKHR",
+        get_physical_device_surface_present_modes_khr:
+            vk::PFN_vkGetPhysicalDeviceSurfacePresentModesKHR =
+            "vkGetPhysicalDeviceSurfacePresentModesKHR",
+    }
+}
+
+dynamic_library::api! {
+    struct Win32SurfaceFn {
+        create_win32_surface_khr: vk::PFN_vkCreateWin32SurfaceKHR = "vkCreateWin32SurfaceKHR",
+    }
+}
+
+dynamic_library::api! {
+    struct XlibSurfaceFn {
+        create_xlib_surface_khr: vk::PFN_vkCreateXlibSurfaceKHR = "vkCreateXlibSurfaceKHR",
+    }
+}
+
+dynamic_library::api! {
+    struct XcbSurfaceFn {
+        create_xcb_surface_khr: vk::PFN_vkCreateXcbSurfaceKHR = "vkCreateXcbSurfaceKHR",
+    }
+}
+
+dynamic_library::api! {
+    struct WaylandSurfaceFn {
+        create_wayland_surface_khr: vk::PFN_vkCreateWaylandSurfaceKHR =
+            "vkCreateWaylandSurfaceKHR",
+    }
+}
+
 pub struct SurfaceKhr {
     handle: vk::SurfaceKHR,
-    surface_fn: vk::KhrSurfaceFn,
-    win32_surface_fn: vk::KhrWin32SurfaceFn,
+    surface_fn: SurfaceFn,
     instance: Instance,
 }
 
 impl SurfaceKhr {
-    pub fn new<W: HasRawWindowHandle>(instance: &Instance, window: &W) -> Self {
-        use raw_window_handle::{RawWindowHandle, Win32WindowHandle};
-
-        let get_instance_proc_addr = unsafe {
-            std::mem::transmute::<_, ash::vk::PFN_vkGetInstanceProcAddr>(
-                instance
-                    .inner
-                    ._lib
-                    .get("vkGetInstanceProcAddr")
-                    .unwrap_unchecked()
-                    .as_ptr(),
-            )
+    pub fn new<W>(instance: &Instance, window: &W) -> Result<Self>
+    where
+        W: HasRawWindowHandle + HasRawDisplayHandle,
+    {
+        use raw_window_handle::{
+            RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle, Win32WindowHandle,
+            XcbDisplayHandle, XcbWindowHandle, XlibDisplayHandle, XlibWindowHandle,
         };
 
-        let loader = |name: &CStr| unsafe {
-            std::mem::transmute(get_instance_proc_addr(
-                instance.inner.handle.handle(),
-                name.as_ptr(),
-            ))
-        };
-        let surface_fn = vk::KhrSurfaceFn::load(loader);
-        let win32_surface_fn = vk::KhrWin32SurfaceFn::load(loader);
+        let loader = |name: &CStr| instance.proc_addr(name);
+        let instance_handle = instance.inner.handle.handle();
 
-        match window.raw_window_handle() {
-            RawWindowHandle::Win32(Win32WindowHandle {
-                hwnd, hinstance, ..
-            }) => {
-                let create_info = vk::Win32SurfaceCreateInfoKHR {
-                    hinstance,
-                    hwnd,
-                    ..Default::default()
-                };
-                let mut handle = MaybeUninit::uninit();
-                unsafe {
-                    let res = (win32_surface_fn.create_win32_surface_khr)(
-                        instance.inner.handle.handle(),
+        // SAFETY: The tables are declared with the `PFN_*` types of their commands.
+        let surface_fn = unsafe { SurfaceFn::load_with(loader)? };
+
+        let mut handle = MaybeUninit::uninit();
+        // SAFETY: The handles come from a live window of the matching window system.
+        let res = unsafe {
+            match (window.raw_display_handle(), window.raw_window_handle()) {
+                (
+                    RawDisplayHandle::Windows(_),
+                    RawWindowHandle::Win32(Win32WindowHandle {
+                        hwnd, hinstance, ..
+                    }),
+                ) => {
+                    let fp = Win32SurfaceFn::load_with(loader)?;
+                    let create_info = vk::Win32SurfaceCreateInfoKHR {
+                        hinstance,
+                        hwnd,
+                        ..Default::default()
+                    };
+
+                    (fp.create_win32_surface_khr)(
+                        instance_handle,
                         &create_info,
                         std::ptr::null(),
                         handle.as_mut_ptr(),
-                    );
-
-                    if res == vk::Result::SUCCESS {
-                        log::trace!("Surface created.");
-                        Self {
-                            handle: handle.assume_init(),
-                            surface_fn,
-                            win32_surface_fn,
-                            instance: instance.clone(),
-                        }
-                    } else {
-                        panic!("Failed to create surface object: {:?}", res)
-                    }
+                    )
+                }
+                (
+                    RawDisplayHandle::Xlib(XlibDisplayHandle { display, .. }),
+                    RawWindowHandle::Xlib(XlibWindowHandle { window, .. }),
+                ) => {
+                    let fp = XlibSurfaceFn::load_with(loader)?;
+                    let create_info = vk::XlibSurfaceCreateInfoKHR {
+                        dpy: display.cast(),
+                        window,
+                        ..Default::default()
+                    };
+
+                    (fp.create_xlib_surface_khr)(
+                        instance_handle,
+                        &create_info,
+                        std::ptr::null(),
+                        handle.as_mut_ptr(),
+                    )
+                }
+                (
+                    RawDisplayHandle::Xcb(XcbDisplayHandle { connection, .. }),
+                    RawWindowHandle::Xcb(XcbWindowHandle { window, .. }),
+                ) => {
+                    let fp = XcbSurfaceFn::load_with(loader)?;
+                    let create_info = vk::XcbSurfaceCreateInfoKHR {
+                        connection,
+                        window,
+                        ..Default::default()
+                    };
+
+                    (fp.create_xcb_surface_khr)(
+                        instance_handle,
+                        &create_info,
+                        std::ptr::null(),
+                        handle.as_mut_ptr(),
+                    )
+                }
+                (
+                    RawDisplayHandle::Wayland(WaylandDisplayHandle { display, .. }),
+                    RawWindowHandle::Wayland(WaylandWindowHandle { surface, .. }),
+                ) => {
+                    let fp = WaylandSurfaceFn::load_with(loader)?;
+                    let create_info = vk::WaylandSurfaceCreateInfoKHR {
+                        display,
+                        surface,
+                        ..Default::default()
+                    };
+
+                    (fp.create_wayland_surface_khr)(
+                        instance_handle,
+                        &create_info,
+                        std::ptr::null(),
+                        handle.as_mut_ptr(),
+                    )
+                }
+                (display, window) => {
+                    return Err(Error::UnsupportedWindow(format!(
+                        "{:?}, {:?}",
+                        display, window
+                    )))
                 }
             }
-            _ => panic!("not supported"),
+        };
+
+        if res != vk::Result::SUCCESS {
+            return Err(res.into());
         }
+
+        log::trace!("Surface created.");
+        Ok(Self {
+            handle: unsafe { handle.assume_init() },
+            surface_fn,
+            instance: instance.clone(),
+        })
     }
 }
 
@@ -535,6 +1777,16 @@ pub struct ExtensionProperties {
     spec_version: u32,
 }
 
+impl ExtensionProperties {
+    pub fn extension_name(&self) -> &str {
+        &self.extension_name
+    }
+
+    pub fn spec_version(&self) -> u32 {
+        self.spec_version
+    }
+}
+
 impl From<vk::ExtensionProperties> for ExtensionProperties {
     fn from(props: vk::ExtensionProperties) -> Self {
         Self {
@@ -552,25 +1804,153 @@ impl From<vk::ExtensionProperties> for ExtensionProperties {
     }
 }
 
+dynamic_library::api! {
+    struct SwapchainFn {
+        create_swapchain_khr: vk::PFN_vkCreateSwapchainKHR = "vkCreateSwapchainKHR",
+        destroy_swapchain_khr: vk::PFN_vkDestroySwapchainKHR = "vkDestroySwapchainKHR",
+        get_swapchain_images_khr: vk::PFN_vkGetSwapchainImagesKHR = "vkGetSwapchainImagesKHR",
+        acquire_next_image_khr: vk::PFN_vkAcquireNextImageKHR = "vkAcquireNextImageKHR",
+        queue_present_khr: vk::PFN_vkQueuePresentKHR = "vkQueuePresentKHR",
+    }
+}
+
 pub struct SwapchainKhr {
     handle: vk::SwapchainKHR,
-    fp: vk::KhrSwapchainFn,
+    fp: SwapchainFn,
+    images: Vec<Image>,
+    image_views: Vec<ImageView>,
+    format: SurfaceFormat,
+    present_mode: PresentMode,
     device: Device,
 }
 
 impl SwapchainKhr {
-    pub fn new(device: &Device, surface: &SurfaceKhr) -> Self {
-        let fp = vk::KhrSwapchainFn::load(|name| unsafe {
-            std::mem::transmute(
-                device
-                    .instance
-                    .inner
-                    .handle
-                    .get_device_proc_addr(device.inner.handle.handle(), name.as_ptr()),
+    /// Creates a swapchain for `surface` from its current capabilities.
+    /// `extent` is only used when the surface lets the swapchain decide its size,
+    /// e.g. on Wayland. The first supported mode in `present_modes` is used,
+    /// falling back to FIFO.
+    pub fn new(
+        device: &Device,
+        physical_device: &PhysicalDevice,
+        surface: &SurfaceKhr,
+        extent: Extent2D,
+        present_modes: &[PresentMode],
+    ) -> Result<Self> {
+        Self::create(
+            device,
+            physical_device,
+            surface,
+            extent,
+            present_modes,
+            vk::SwapchainKHR::null(),
+        )
+    }
+
+    /// Creates a replacement for this swapchain, e.g. after the surface was
+    /// resized. Images already presented from `self` are still shown until the
+    /// new swapchain takes over, after which `self` should be dropped.
+    pub fn recreate(
+        &self,
+        physical_device: &PhysicalDevice,
+        surface: &SurfaceKhr,
+        extent: Extent2D,
+        present_modes: &[PresentMode],
+    ) -> Result<Self> {
+        Self::create(
+            &self.device,
+            physical_device,
+            surface,
+            extent,
+            present_modes,
+            self.handle,
+        )
+    }
+
+    fn create(
+        device: &Device,
+        physical_device: &PhysicalDevice,
+        surface: &SurfaceKhr,
+        extent: Extent2D,
+        present_modes: &[PresentMode],
+        old_swapchain: vk::SwapchainKHR,
+    ) -> Result<Self> {
+        // SAFETY: The table is declared with the `PFN_*` types of its commands.
+        let fp = unsafe { SwapchainFn::load_with(|name| device.proc_addr(name))? };
+
+        let capabilities = physical_device.surface_capabilities(surface)?;
+        let format = choose_surface_format(&physical_device.surface_formats(surface)?)
+            .ok_or(Error::from(vk::Result::ERROR_FORMAT_NOT_SUPPORTED))?;
+        let present_mode = choose_present_mode(
+            present_modes,
+            &physical_device.surface_present_modes(surface)?,
+        );
+        log::info!("Present mode: {:?}", present_mode);
+
+        let min_image_count = match capabilities.max_image_count() {
+            Some(max) => (capabilities.min_image_count() + 1).min(max),
+            None => capabilities.min_image_count() + 1,
+        };
+        let image_extent = capabilities.current_extent().unwrap_or_else(|| {
+            let (min, max) = (
+                capabilities.min_image_extent(),
+                capabilities.max_image_extent(),
+            );
+            Extent2D::new(
+                extent.width().clamp(min.width(), max.width()),
+                extent.height().clamp(min.height(), max.height()),
             )
         });
+        let supported_alpha = capabilities.supported_composite_alpha();
+        let composite_alpha = if supported_alpha.opaque() {
+            vk::CompositeAlphaFlagsKHR::OPAQUE
+        } else if supported_alpha.pre_multiplied() {
+            vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED
+        } else if supported_alpha.post_multiplied() {
+            vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED
+        } else {
+            vk::CompositeAlphaFlagsKHR::INHERIT
+        };
+        // Frames are cleared with a transfer command until there is a render pass.
+        if !capabilities.supported_usage().transfer_dst() {
+            return Err(Error::UnsupportedSurfaceUsage("TRANSFER_DST"));
+        }
+        let image_usage = vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_DST;
+
+        // Images are shared rather than transferred between families when
+        // presentation has its own.
+        let queue_families = device.inner.queue_families;
+        let shared_families = match queue_families.present() {
+            Some(present) if present != queue_families.graphics() => {
+                [queue_families.graphics(), present]
+                    .map(|i| u32::try_from(i).expect("Could not convert `usize` to `u32`"))
+                    .to_vec()
+            }
+            _ => Vec::new(),
+        };
 
         let create_info = vk::SwapchainCreateInfoKHR {
+            surface: surface.handle,
+            min_image_count,
+            image_format: format.format().into(),
+            image_color_space: format.color_space().into(),
+            image_extent: image_extent.into(),
+            image_array_layers: 1,
+            image_usage,
+            image_sharing_mode: if shared_families.is_empty() {
+                vk::SharingMode::EXCLUSIVE
+            } else {
+                vk::SharingMode::CONCURRENT
+            },
+            queue_family_index_count: shared_families
+                .len()
+                .try_into()
+                .expect("Could not convert `usize` to `u32`"),
+            p_queue_family_indices: shared_families.as_ptr(),
+            pre_transform: capabilities.current_transform().into(),
+            composite_alpha,
+            present_mode: present_mode.into(),
+            clipped: vk::TRUE,
+            old_swapchain,
             ..Default::default()
         };
 
@@ -583,21 +1963,134 @@ impl SwapchainKhr {
                 handle.as_mut_ptr(),
             )
         };
+        if res != vk::Result::SUCCESS {
+            return Err(res.into());
+        }
+        let handle = unsafe { handle.assume_init() };
+        log::trace!("Swapchain created.");
 
-        if res == vk::Result::SUCCESS {
-            Self {
-                handle: unsafe { handle.assume_init() },
-                fp,
-                device: device.clone(),
-            }
-        } else {
-            panic!("Failed to create swapchain: {}", res)
+        let mut swapchain = Self {
+            handle,
+            fp,
+            images: Vec::new(),
+            image_views: Vec::new(),
+            format,
+            present_mode,
+            device: device.clone(),
+        };
+
+        // SAFETY: The command writes at most `count` elements to the pointer.
+        let images = unsafe {
+            enumerate(|count, data| {
+                (swapchain.fp.get_swapchain_images_khr)(
+                    device.inner.handle.handle(),
+                    handle,
+                    count,
+                    data,
+                )
+            })?
+        };
+        swapchain.images = images
+            .into_iter()
+            .map(|image| {
+                Image::from_swapchain(device, image, image_extent.into(), format.format().into())
+            })
+            .collect();
+        swapchain.image_views = swapchain
+            .images
+            .iter()
+            .map(ImageView::new)
+            .collect::<Result<_>>()?;
+
+        Ok(swapchain)
+    }
+
+    /// Also names the images as `"{name} image {index}"`.
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.device.set_object_name(self.handle, name)?;
+        for (i, image) in self.images.iter().enumerate() {
+            image.set_debug_name(&format!("{} image {}", name, i))?;
+        }
+        for (i, view) in self.image_views.iter().enumerate() {
+            view.set_debug_name(&format!("{} image view {}", name, i))?;
+        }
+        Ok(())
+    }
+
+    pub fn images(&self) -> &[Image] {
+        &self.images
+    }
+
+    pub fn image_views(&self) -> &[ImageView] {
+        &self.image_views
+    }
+
+    pub fn format(&self) -> SurfaceFormat {
+        self.format
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        self.present_mode
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.images
+            .first()
+            .map_or(vk::Extent2D::default(), Image::extent)
+    }
+
+    /// Index of the next image to draw into, and whether the swapchain no
+    /// longer matches the surface exactly. `semaphore` is signaled once the
+    /// presentation engine is done with the image.
+    pub fn acquire_next_image(&self, semaphore: &Semaphore) -> Result<(usize, bool)> {
+        let mut index = 0;
+        let res = unsafe {
+            (self.fp.acquire_next_image_khr)(
+                self.device.inner.handle.handle(),
+                self.handle,
+                u64::MAX,
+                semaphore.handle,
+                vk::Fence::null(),
+                &mut index,
+            )
+        };
+
+        match res {
+            vk::Result::SUCCESS => Ok((index as usize, false)),
+            vk::Result::SUBOPTIMAL_KHR => Ok((index as usize, true)),
+            code => Err(code.into()),
+        }
+    }
+
+    /// Queues image `index` for presentation once `wait` is signaled. Returns
+    /// whether the swapchain no longer matches the surface exactly.
+    pub fn present(&self, queue: &Queue, index: usize, wait: &Semaphore) -> Result<bool> {
+        let image_index: u32 = index
+            .try_into()
+            .expect("Could not convert `usize` to `u32`");
+        let present_info = vk::PresentInfoKHR {
+            wait_semaphore_count: 1,
+            p_wait_semaphores: &wait.handle,
+            swapchain_count: 1,
+            p_swapchains: &self.handle,
+            p_image_indices: &image_index,
+            ..Default::default()
+        };
+
+        let handle = queue.lock();
+        match unsafe { (self.fp.queue_present_khr)(*handle, &present_info) } {
+            vk::Result::SUCCESS => Ok(false),
+            vk::Result::SUBOPTIMAL_KHR => Ok(true),
+            code => Err(code.into()),
         }
     }
 }
 
 impl Drop for SwapchainKhr {
     fn drop(&mut self) {
+        // The views must go before the images they refer to.
+        self.image_views.clear();
+        self.images.clear();
         unsafe {
             (self.fp.destroy_swapchain_khr)(
                 self.device.inner.handle.handle(),
@@ -605,55 +2098,99 @@ impl Drop for SwapchainKhr {
                 std::ptr::null(),
             );
         }
+        log::trace!("Swapchain destroyed.");
     }
 }
 
-pub struct SurfaceTransformKhr {
-    identity: bool,
-    rotate_90: bool,
-    rotate_180: bool,
-    rotate_270: bool,
-    horizontal_mirror: bool,
-    horizontal_mirror_rotate_90: bool,
-    horizontal_mirror_rotate_180: bool,
-    horizontal_mirror_rotate_270: bool,
-    inherit: bool,
-}
-
-impl SurfaceTransformKhr {
-    pub fn identity(&self) -> bool {
-        self.identity
-    }
+/// Prefers 8-bit BGRA sRGB, which is what most compositors scan out directly.
+fn choose_surface_format(formats: &[SurfaceFormat]) -> Option<SurfaceFormat> {
+    let preferred = SurfaceFormat::new(Format::B8G8R8A8Srgb, ColorSpace::SrgbNonlinear);
 
-    pub fn rotate_90(&self) -> bool {
-        self.rotate_90
-    }
+    formats
+        .iter()
+        .find(|&&f| f == preferred)
+        .or_else(|| formats.first())
+        .copied()
+}
 
-    pub fn rotate_180(&self) -> bool {
-        self.rotate_180
-    }
+/// The first of `preferred` that is `available`, or FIFO, which is the only
+/// mode every implementation has to support.
+fn choose_present_mode(preferred: &[PresentMode], available: &[PresentMode]) -> PresentMode {
+    preferred
+        .iter()
+        .copied()
+        .find(|m| available.contains(m))
+        .unwrap_or(PresentMode::Fifo)
+}
 
-    pub fn rotate_270(&self) -> bool {
-        self.rotate_270
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_api_version() {
+        let loader = vk::make_api_version(0, 1, 3, 250);
+        let (v1_0, v1_2, v1_3) = (
+            vk::API_VERSION_1_0,
+            vk::API_VERSION_1_2,
+            vk::API_VERSION_1_3,
+        );
+
+        assert_eq!(negotiate_api_version(loader, v1_0, v1_3), Ok(v1_3));
+        assert_eq!(negotiate_api_version(loader, v1_0, v1_2), Ok(v1_2));
+        assert_eq!(negotiate_api_version(v1_0, v1_0, v1_3), Ok(v1_0));
+        assert_eq!(
+            negotiate_api_version(vk::make_api_version(0, 1, 1, 120), v1_0, v1_3),
+            Ok(vk::API_VERSION_1_1)
+        );
     }
 
-    pub fn horizontal_mirror(&self) -> bool {
-        self.horizontal_mirror
-    }
+    #[test]
+    fn rejects_old_loader() {
+        let loader = vk::make_api_version(0, 1, 1, 120);
 
-    pub fn horizontal_mirror_rotate_90(&self) -> bool {
-        self.horizontal_mirror_rotate_90
+        assert_eq!(
+            negotiate_api_version(loader, vk::API_VERSION_1_2, vk::API_VERSION_1_3),
+            Err(Error::UnsupportedApiVersion {
+                required: vk::API_VERSION_1_2,
+                supported: loader,
+            })
+        );
     }
 
-    pub fn horizontal_mirror_rotate_180(&self) -> bool {
-        self.horizontal_mirror_rotate_180
+    #[test]
+    fn chooses_first_available_present_mode() {
+        let available = [
+            PresentMode::Fifo,
+            PresentMode::Mailbox,
+            PresentMode::Immediate,
+        ];
+
+        assert_eq!(
+            choose_present_mode(&[PresentMode::Mailbox, PresentMode::Immediate], &available),
+            PresentMode::Mailbox
+        );
+        assert_eq!(
+            choose_present_mode(&[PresentMode::Immediate, PresentMode::Mailbox], &available),
+            PresentMode::Immediate
+        );
+        assert_eq!(
+            choose_present_mode(
+                &[PresentMode::FifoRelaxed, PresentMode::Mailbox],
+                &available
+            ),
+            PresentMode::Mailbox
+        );
     }
 
-    pub fn horizontal_mirror_rotate_270(&self) -> bool {
-        self.horizontal_mirror_rotate_270
-    }
+    #[test]
+    fn falls_back_to_fifo() {
+        let available = [PresentMode::Fifo, PresentMode::FifoRelaxed];
 
-    pub fn inherit(&self) -> bool {
-        self.inherit
+        assert_eq!(
+            choose_present_mode(&[PresentMode::Mailbox, PresentMode::Immediate], &available),
+            PresentMode::Fifo
+        );
+        assert_eq!(choose_present_mode(&[], &available), PresentMode::Fifo);
     }
 }
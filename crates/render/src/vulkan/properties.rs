@@ -0,0 +1,817 @@
+use ash::vk;
+
+use std::ffi::CStr;
+
+/// Converts a field of a raw Vulkan struct to the type it is exposed as.
+trait FromRaw<T> {
+    fn from_raw(raw: T) -> Self;
+}
+
+impl<T> FromRaw<T> for T {
+    fn from_raw(raw: T) -> Self {
+        raw
+    }
+}
+
+impl FromRaw<vk::Bool32> for bool {
+    fn from_raw(raw: vk::Bool32) -> Self {
+        raw != vk::FALSE
+    }
+}
+
+impl FromRaw<vk::SampleCountFlags> for SampleCounts {
+    fn from_raw(raw: vk::SampleCountFlags) -> Self {
+        raw.into()
+    }
+}
+
+/// The field of `$raw` that `$field` is converted from, which has the same
+/// name unless another one is given.
+macro_rules! raw_field {
+    ($raw:ident, $field:ident) => {
+        $raw.$field
+    };
+    ($raw:ident, $field:ident, $raw_field:ident) => {
+        $raw.$raw_field
+    };
+}
+
+/// Declares a struct with a getter for each field, converted from the raw
+/// Vulkan struct.
+macro_rules! properties {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident from $raw:ty {
+            $($field:ident: $ty:ty $(= $raw_field:ident)?,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            $($field: $ty,)*
+        }
+
+        impl $name {
+            $(
+                pub fn $field(&self) -> $ty {
+                    self.$field
+                }
+            )*
+        }
+
+        impl From<$raw> for $name {
+            fn from(raw: $raw) -> Self {
+                Self {
+                    $($field: FromRaw::from_raw(raw_field!(raw, $field $(, $raw_field)?)),)*
+                }
+            }
+        }
+    };
+}
+
+/// Declares a set of boolean features that converts to and from the raw
+/// Vulkan struct. The fields are public so that the features to enable can be
+/// written as a struct literal.
+macro_rules! features {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident from $raw:ty {
+            $($field:ident $(= $raw_field:ident)?,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub struct $name {
+            $(pub $field: bool,)*
+        }
+
+        impl $name {
+            /// Pushes the name of every feature of `self` that `available` lacks.
+            fn missing(&self, available: &Self, missing: &mut Vec<&'static str>) {
+                $(
+                    if self.$field && !available.$field {
+                        missing.push(stringify!($field));
+                    }
+                )*
+            }
+
+            fn union(&self, other: &Self) -> Self {
+                Self {
+                    $($field: self.$field || other.$field,)*
+                }
+            }
+
+            fn intersection(&self, other: &Self) -> Self {
+                Self {
+                    $($field: self.$field && other.$field,)*
+                }
+            }
+        }
+
+        impl From<$raw> for $name {
+            fn from(raw: $raw) -> Self {
+                Self {
+                    $($field: FromRaw::from_raw(raw_field!(raw, $field $(, $raw_field)?)),)*
+                }
+            }
+        }
+
+        impl From<$name> for $raw {
+            fn from(features: $name) -> Self {
+                let mut raw = Self::default();
+                $(raw_field!(raw, $field $(, $raw_field)?) = features.$field.into();)*
+                raw
+            }
+        }
+    };
+}
+
+/// What [`PhysicalDevice::properties`](super::PhysicalDevice::properties)
+/// reports about a device.
+#[derive(Debug, Clone)]
+pub struct PhysicalDeviceProperties {
+    api_version: u32,
+    driver_version: u32,
+    vendor_id: u32,
+    device_id: u32,
+    device_type: PhysicalDeviceType,
+    device_name: String,
+    pipeline_cache_uuid: [u8; vk::UUID_SIZE],
+    limits: PhysicalDeviceLimits,
+}
+
+impl PhysicalDeviceProperties {
+    /// Highest Vulkan version supported by the device, as made by
+    /// `vk::make_api_version`.
+    pub fn api_version(&self) -> u32 {
+        self.api_version
+    }
+
+    /// Encoding is vendor specific.
+    pub fn driver_version(&self) -> u32 {
+        self.driver_version
+    }
+
+    /// PCI vendor ID, or a Khronos vendor ID for vendors without one.
+    pub fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+
+    pub fn device_id(&self) -> u32 {
+        self.device_id
+    }
+
+    pub fn device_type(&self) -> PhysicalDeviceType {
+        self.device_type
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    /// Identifies pipeline caches that can be reused with this device.
+    pub fn pipeline_cache_uuid(&self) -> [u8; vk::UUID_SIZE] {
+        self.pipeline_cache_uuid
+    }
+
+    pub fn limits(&self) -> &PhysicalDeviceLimits {
+        &self.limits
+    }
+}
+
+impl From<vk::PhysicalDeviceProperties> for PhysicalDeviceProperties {
+    fn from(props: vk::PhysicalDeviceProperties) -> Self {
+        Self {
+            api_version: props.api_version,
+            driver_version: props.driver_version,
+            vendor_id: props.vendor_id,
+            device_id: props.device_id,
+            device_type: props.device_type.into(),
+            // SAFETY: According to the Vulkan Specification, `device_name` is
+            // null-terminated UTF-8 string.
+            device_name: unsafe {
+                String::from_utf8_unchecked(
+                    CStr::from_ptr(props.device_name.as_ptr()).to_bytes().into(),
+                )
+            },
+            pipeline_cache_uuid: props.pipeline_cache_uuid,
+            limits: props.limits.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicalDeviceType {
+    Other,
+    IntegratedGpu,
+    DiscreteGpu,
+    VirtualGpu,
+    Cpu,
+}
+
+impl From<vk::PhysicalDeviceType> for PhysicalDeviceType {
+    fn from(t: vk::PhysicalDeviceType) -> Self {
+        match t {
+            vk::PhysicalDeviceType::OTHER => Self::Other,
+            vk::PhysicalDeviceType::INTEGRATED_GPU => Self::IntegratedGpu,
+            vk::PhysicalDeviceType::DISCRETE_GPU => Self::DiscreteGpu,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => Self::VirtualGpu,
+            vk::PhysicalDeviceType::CPU => Self::Cpu,
+            _ => unsafe { std::hint::unreachable_unchecked() },
+        }
+    }
+}
+
+properties! {
+    /// Implementation limits of a physical device.
+    pub struct PhysicalDeviceLimits from vk::PhysicalDeviceLimits {
+        max_image_dimension_1d: u32 = max_image_dimension1_d,
+        max_image_dimension_2d: u32 = max_image_dimension2_d,
+        max_image_dimension_3d: u32 = max_image_dimension3_d,
+        max_image_dimension_cube: u32,
+        max_image_array_layers: u32,
+        max_texel_buffer_elements: u32,
+        max_uniform_buffer_range: u32,
+        max_storage_buffer_range: u32,
+        max_push_constants_size: u32,
+        max_memory_allocation_count: u32,
+        max_sampler_allocation_count: u32,
+        buffer_image_granularity: u64,
+        sparse_address_space_size: u64,
+        max_bound_descriptor_sets: u32,
+        max_per_stage_descriptor_samplers: u32,
+        max_per_stage_descriptor_uniform_buffers: u32,
+        max_per_stage_descriptor_storage_buffers: u32,
+        max_per_stage_descriptor_sampled_images: u32,
+        max_per_stage_descriptor_storage_images: u32,
+        max_per_stage_descriptor_input_attachments: u32,
+        max_per_stage_resources: u32,
+        max_descriptor_set_samplers: u32,
+        max_descriptor_set_uniform_buffers: u32,
+        max_descriptor_set_uniform_buffers_dynamic: u32,
+        max_descriptor_set_storage_buffers: u32,
+        max_descriptor_set_storage_buffers_dynamic: u32,
+        max_descriptor_set_sampled_images: u32,
+        max_descriptor_set_storage_images: u32,
+        max_descriptor_set_input_attachments: u32,
+        max_vertex_input_attributes: u32,
+        max_vertex_input_bindings: u32,
+        max_vertex_input_attribute_offset: u32,
+        max_vertex_input_binding_stride: u32,
+        max_vertex_output_components: u32,
+        max_tessellation_generation_level: u32,
+        max_tessellation_patch_size: u32,
+        max_tessellation_control_per_vertex_input_components: u32,
+        max_tessellation_control_per_vertex_output_components: u32,
+        max_tessellation_control_per_patch_output_components: u32,
+        max_tessellation_control_total_output_components: u32,
+        max_tessellation_evaluation_input_components: u32,
+        max_tessellation_evaluation_output_components: u32,
+        max_geometry_shader_invocations: u32,
+        max_geometry_input_components: u32,
+        max_geometry_output_components: u32,
+        max_geometry_output_vertices: u32,
+        max_geometry_total_output_components: u32,
+        max_fragment_input_components: u32,
+        max_fragment_output_attachments: u32,
+        max_fragment_dual_src_attachments: u32,
+        max_fragment_combined_output_resources: u32,
+        max_compute_shared_memory_size: u32,
+        max_compute_work_group_count: [u32; 3],
+        max_compute_work_group_invocations: u32,
+        max_compute_work_group_size: [u32; 3],
+        sub_pixel_precision_bits: u32,
+        sub_texel_precision_bits: u32,
+        mipmap_precision_bits: u32,
+        max_draw_indexed_index_value: u32,
+        max_draw_indirect_count: u32,
+        max_sampler_lod_bias: f32,
+        max_sampler_anisotropy: f32,
+        max_viewports: u32,
+        max_viewport_dimensions: [u32; 2],
+        viewport_bounds_range: [f32; 2],
+        viewport_sub_pixel_bits: u32,
+        min_memory_map_alignment: usize,
+        min_texel_buffer_offset_alignment: u64,
+        min_uniform_buffer_offset_alignment: u64,
+        min_storage_buffer_offset_alignment: u64,
+        min_texel_offset: i32,
+        max_texel_offset: u32,
+        min_texel_gather_offset: i32,
+        max_texel_gather_offset: u32,
+        min_interpolation_offset: f32,
+        max_interpolation_offset: f32,
+        sub_pixel_interpolation_offset_bits: u32,
+        max_framebuffer_width: u32,
+        max_framebuffer_height: u32,
+        max_framebuffer_layers: u32,
+        framebuffer_color_sample_counts: SampleCounts,
+        framebuffer_depth_sample_counts: SampleCounts,
+        framebuffer_stencil_sample_counts: SampleCounts,
+        framebuffer_no_attachments_sample_counts: SampleCounts,
+        max_color_attachments: u32,
+        sampled_image_color_sample_counts: SampleCounts,
+        sampled_image_integer_sample_counts: SampleCounts,
+        sampled_image_depth_sample_counts: SampleCounts,
+        sampled_image_stencil_sample_counts: SampleCounts,
+        storage_image_sample_counts: SampleCounts,
+        max_sample_mask_words: u32,
+        timestamp_compute_and_graphics: bool,
+        timestamp_period: f32,
+        max_clip_distances: u32,
+        max_cull_distances: u32,
+        max_combined_clip_and_cull_distances: u32,
+        discrete_queue_priorities: u32,
+        point_size_range: [f32; 2],
+        line_width_range: [f32; 2],
+        point_size_granularity: f32,
+        line_width_granularity: f32,
+        strict_lines: bool,
+        standard_sample_locations: bool,
+        optimal_buffer_copy_offset_alignment: u64,
+        optimal_buffer_copy_row_pitch_alignment: u64,
+        non_coherent_atom_size: u64,
+    }
+}
+
+/// Sample counts supported for some kind of image or attachment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SampleCounts {
+    count_1: bool,
+    count_2: bool,
+    count_4: bool,
+    count_8: bool,
+    count_16: bool,
+    count_32: bool,
+    count_64: bool,
+}
+
+impl SampleCounts {
+    pub fn count_1(&self) -> bool {
+        self.count_1
+    }
+
+    pub fn count_2(&self) -> bool {
+        self.count_2
+    }
+
+    pub fn count_4(&self) -> bool {
+        self.count_4
+    }
+
+    pub fn count_8(&self) -> bool {
+        self.count_8
+    }
+
+    pub fn count_16(&self) -> bool {
+        self.count_16
+    }
+
+    pub fn count_32(&self) -> bool {
+        self.count_32
+    }
+
+    pub fn count_64(&self) -> bool {
+        self.count_64
+    }
+
+    /// The highest supported count, or `None` if there is none.
+    pub fn max(&self) -> Option<u32> {
+        [
+            (self.count_64, 64),
+            (self.count_32, 32),
+            (self.count_16, 16),
+            (self.count_8, 8),
+            (self.count_4, 4),
+            (self.count_2, 2),
+            (self.count_1, 1),
+        ]
+        .into_iter()
+        .find_map(|(supported, count)| supported.then_some(count))
+    }
+}
+
+impl From<vk::SampleCountFlags> for SampleCounts {
+    fn from(flags: vk::SampleCountFlags) -> Self {
+        Self {
+            count_1: flags.contains(vk::SampleCountFlags::TYPE_1),
+            count_2: flags.contains(vk::SampleCountFlags::TYPE_2),
+            count_4: flags.contains(vk::SampleCountFlags::TYPE_4),
+            count_8: flags.contains(vk::SampleCountFlags::TYPE_8),
+            count_16: flags.contains(vk::SampleCountFlags::TYPE_16),
+            count_32: flags.contains(vk::SampleCountFlags::TYPE_32),
+            count_64: flags.contains(vk::SampleCountFlags::TYPE_64),
+        }
+    }
+}
+
+features! {
+    /// Features of Vulkan 1.0.
+    pub struct PhysicalDeviceFeatures from vk::PhysicalDeviceFeatures {
+        robust_buffer_access,
+        full_draw_index_uint32,
+        image_cube_array,
+        independent_blend,
+        geometry_shader,
+        tessellation_shader,
+        sample_rate_shading,
+        dual_src_blend,
+        logic_op,
+        multi_draw_indirect,
+        draw_indirect_first_instance,
+        depth_clamp,
+        depth_bias_clamp,
+        fill_mode_non_solid,
+        depth_bounds,
+        wide_lines,
+        large_points,
+        alpha_to_one,
+        multi_viewport,
+        sampler_anisotropy,
+        texture_compression_etc2,
+        texture_compression_astc_ldr,
+        texture_compression_bc,
+        occlusion_query_precise,
+        pipeline_statistics_query,
+        vertex_pipeline_stores_and_atomics,
+        fragment_stores_and_atomics,
+        shader_tessellation_and_geometry_point_size,
+        shader_image_gather_extended,
+        shader_storage_image_extended_formats,
+        shader_storage_image_multisample,
+        shader_storage_image_read_without_format,
+        shader_storage_image_write_without_format,
+        shader_uniform_buffer_array_dynamic_indexing,
+        shader_sampled_image_array_dynamic_indexing,
+        shader_storage_buffer_array_dynamic_indexing,
+        shader_storage_image_array_dynamic_indexing,
+        shader_clip_distance,
+        shader_cull_distance,
+        shader_float64,
+        shader_int64,
+        shader_int16,
+        shader_resource_residency,
+        shader_resource_min_lod,
+        sparse_binding,
+        sparse_residency_buffer,
+        sparse_residency_image_2d = sparse_residency_image2_d,
+        sparse_residency_image_3d = sparse_residency_image3_d,
+        sparse_residency2_samples,
+        sparse_residency4_samples,
+        sparse_residency8_samples,
+        sparse_residency16_samples,
+        sparse_residency_aliased,
+        variable_multisample_rate,
+        inherited_queries,
+    }
+}
+
+features! {
+    /// Features promoted to core in Vulkan 1.1.
+    pub struct PhysicalDeviceVulkan11Features from vk::PhysicalDeviceVulkan11Features {
+        storage_buffer16_bit_access,
+        uniform_and_storage_buffer16_bit_access,
+        storage_push_constant16,
+        storage_input_output16,
+        multiview,
+        multiview_geometry_shader,
+        multiview_tessellation_shader,
+        variable_pointers_storage_buffer,
+        variable_pointers,
+        protected_memory,
+        sampler_ycbcr_conversion,
+        shader_draw_parameters,
+    }
+}
+
+features! {
+    /// Features promoted to core in Vulkan 1.2.
+    pub struct PhysicalDeviceVulkan12Features from vk::PhysicalDeviceVulkan12Features {
+        sampler_mirror_clamp_to_edge,
+        draw_indirect_count,
+        storage_buffer8_bit_access,
+        uniform_and_storage_buffer8_bit_access,
+        storage_push_constant8,
+        shader_buffer_int64_atomics,
+        shader_shared_int64_atomics,
+        shader_float16,
+        shader_int8,
+        descriptor_indexing,
+        shader_input_attachment_array_dynamic_indexing,
+        shader_uniform_texel_buffer_array_dynamic_indexing,
+        shader_storage_texel_buffer_array_dynamic_indexing,
+        shader_uniform_buffer_array_non_uniform_indexing,
+        shader_sampled_image_array_non_uniform_indexing,
+        shader_storage_buffer_array_non_uniform_indexing,
+        shader_storage_image_array_non_uniform_indexing,
+        shader_input_attachment_array_non_uniform_indexing,
+        shader_uniform_texel_buffer_array_non_uniform_indexing,
+        shader_storage_texel_buffer_array_non_uniform_indexing,
+        descriptor_binding_uniform_buffer_update_after_bind,
+        descriptor_binding_sampled_image_update_after_bind,
+        descriptor_binding_storage_image_update_after_bind,
+        descriptor_binding_storage_buffer_update_after_bind,
+        descriptor_binding_uniform_texel_buffer_update_after_bind,
+        descriptor_binding_storage_texel_buffer_update_after_bind,
+        descriptor_binding_update_unused_while_pending,
+        descriptor_binding_partially_bound,
+        descriptor_binding_variable_descriptor_count,
+        runtime_descriptor_array,
+        sampler_filter_minmax,
+        scalar_block_layout,
+        imageless_framebuffer,
+        uniform_buffer_standard_layout,
+        shader_subgroup_extended_types,
+        separate_depth_stencil_layouts,
+        host_query_reset,
+        timeline_semaphore,
+        buffer_device_address,
+        buffer_device_address_capture_replay,
+        buffer_device_address_multi_device,
+        vulkan_memory_model,
+        vulkan_memory_model_device_scope,
+        vulkan_memory_model_availability_visibility_chains,
+        shader_output_viewport_index,
+        shader_output_layer,
+        subgroup_broadcast_dynamic_id,
+    }
+}
+
+features! {
+    /// Features promoted to core in Vulkan 1.3.
+    pub struct PhysicalDeviceVulkan13Features from vk::PhysicalDeviceVulkan13Features {
+        robust_image_access,
+        inline_uniform_block,
+        descriptor_binding_inline_uniform_block_update_after_bind,
+        pipeline_creation_cache_control,
+        private_data,
+        shader_demote_to_helper_invocation,
+        shader_terminate_invocation,
+        subgroup_size_control,
+        compute_full_subgroups,
+        synchronization2,
+        texture_compression_astc_hdr,
+        shader_zero_initialize_workgroup_memory,
+        dynamic_rendering,
+        shader_integer_dot_product,
+        maintenance4,
+    }
+}
+
+/// Features of every Vulkan version, as supported by a physical device or to
+/// be enabled on a device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceFeatures {
+    pub core: PhysicalDeviceFeatures,
+    pub vulkan_11: PhysicalDeviceVulkan11Features,
+    pub vulkan_12: PhysicalDeviceVulkan12Features,
+    pub vulkan_13: PhysicalDeviceVulkan13Features,
+}
+
+impl DeviceFeatures {
+    /// Names of the enabled features.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.missing(&Self::default())
+    }
+
+    /// Names of the features enabled in `self` but not in `available`.
+    pub fn missing(&self, available: &Self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        self.core.missing(&available.core, &mut missing);
+        self.vulkan_11.missing(&available.vulkan_11, &mut missing);
+        self.vulkan_12.missing(&available.vulkan_12, &mut missing);
+        self.vulkan_13.missing(&available.vulkan_13, &mut missing);
+        missing
+    }
+
+    /// Features enabled in either `self` or `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            core: self.core.union(&other.core),
+            vulkan_11: self.vulkan_11.union(&other.vulkan_11),
+            vulkan_12: self.vulkan_12.union(&other.vulkan_12),
+            vulkan_13: self.vulkan_13.union(&other.vulkan_13),
+        }
+    }
+
+    /// Features enabled in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            core: self.core.intersection(&other.core),
+            vulkan_11: self.vulkan_11.intersection(&other.vulkan_11),
+            vulkan_12: self.vulkan_12.intersection(&other.vulkan_12),
+            vulkan_13: self.vulkan_13.intersection(&other.vulkan_13),
+        }
+    }
+}
+
+/// Memory heaps of a physical device and the memory types allocated from them.
+#[derive(Debug, Clone)]
+pub struct PhysicalDeviceMemoryProperties {
+    memory_types: Vec<MemoryType>,
+    memory_heaps: Vec<MemoryHeap>,
+}
+
+impl PhysicalDeviceMemoryProperties {
+    /// Indexed by memory type index.
+    pub fn memory_types(&self) -> &[MemoryType] {
+        &self.memory_types
+    }
+
+    pub fn memory_heaps(&self) -> &[MemoryHeap] {
+        &self.memory_heaps
+    }
+}
+
+impl From<vk::PhysicalDeviceMemoryProperties> for PhysicalDeviceMemoryProperties {
+    fn from(props: vk::PhysicalDeviceMemoryProperties) -> Self {
+        Self {
+            memory_types: props.memory_types[..props.memory_type_count as usize]
+                .iter()
+                .map(|&t| t.into())
+                .collect(),
+            memory_heaps: props.memory_heaps[..props.memory_heap_count as usize]
+                .iter()
+                .map(|&h| h.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryType {
+    properties: MemoryProperties,
+    heap_index: usize,
+}
+
+impl MemoryType {
+    pub fn properties(&self) -> MemoryProperties {
+        self.properties
+    }
+
+    /// Index into [`PhysicalDeviceMemoryProperties::memory_heaps`].
+    pub fn heap_index(&self) -> usize {
+        self.heap_index
+    }
+}
+
+impl From<vk::MemoryType> for MemoryType {
+    fn from(t: vk::MemoryType) -> Self {
+        Self {
+            properties: t.property_flags.into(),
+            heap_index: t.heap_index as usize,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryProperties {
+    device_local: bool,
+    host_visible: bool,
+    host_coherent: bool,
+    host_cached: bool,
+    lazily_allocated: bool,
+    protected: bool,
+}
+
+impl MemoryProperties {
+    pub fn device_local(&self) -> bool {
+        self.device_local
+    }
+
+    pub fn host_visible(&self) -> bool {
+        self.host_visible
+    }
+
+    pub fn host_coherent(&self) -> bool {
+        self.host_coherent
+    }
+
+    pub fn host_cached(&self) -> bool {
+        self.host_cached
+    }
+
+    pub fn lazily_allocated(&self) -> bool {
+        self.lazily_allocated
+    }
+
+    pub fn protected(&self) -> bool {
+        self.protected
+    }
+}
+
+impl From<vk::MemoryPropertyFlags> for MemoryProperties {
+    fn from(flags: vk::MemoryPropertyFlags) -> Self {
+        Self {
+            device_local: flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            host_visible: flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE),
+            host_coherent: flags.contains(vk::MemoryPropertyFlags::HOST_COHERENT),
+            host_cached: flags.contains(vk::MemoryPropertyFlags::HOST_CACHED),
+            lazily_allocated: flags.contains(vk::MemoryPropertyFlags::LAZILY_ALLOCATED),
+            protected: flags.contains(vk::MemoryPropertyFlags::PROTECTED),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryHeap {
+    size: u64,
+    device_local: bool,
+    multi_instance: bool,
+}
+
+impl MemoryHeap {
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn device_local(&self) -> bool {
+        self.device_local
+    }
+
+    pub fn multi_instance(&self) -> bool {
+        self.multi_instance
+    }
+}
+
+impl From<vk::MemoryHeap> for MemoryHeap {
+    fn from(heap: vk::MemoryHeap) -> Self {
+        Self {
+            size: heap.size,
+            device_local: heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL),
+            multi_instance: heap.flags.contains(vk::MemoryHeapFlags::MULTI_INSTANCE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(
+        geometry_shader: bool,
+        multiview: bool,
+        timeline_semaphore: bool,
+        dynamic_rendering: bool,
+    ) -> DeviceFeatures {
+        DeviceFeatures {
+            core: PhysicalDeviceFeatures {
+                geometry_shader,
+                ..Default::default()
+            },
+            vulkan_11: PhysicalDeviceVulkan11Features {
+                multiview,
+                ..Default::default()
+            },
+            vulkan_12: PhysicalDeviceVulkan12Features {
+                timeline_semaphore,
+                ..Default::default()
+            },
+            vulkan_13: PhysicalDeviceVulkan13Features {
+                dynamic_rendering,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn names_every_enabled_feature() {
+        assert!(DeviceFeatures::default().names().is_empty());
+        assert_eq!(
+            features(true, true, true, true).names(),
+            [
+                "geometry_shader",
+                "multiview",
+                "timeline_semaphore",
+                "dynamic_rendering"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_features_are_those_not_available() {
+        let required = features(true, true, false, true);
+        let available = features(true, false, true, false);
+
+        assert_eq!(
+            required.missing(&available),
+            ["multiview", "dynamic_rendering"]
+        );
+        assert!(required.missing(&required).is_empty());
+        assert!(DeviceFeatures::default().missing(&available).is_empty());
+    }
+
+    #[test]
+    fn union_and_intersection() {
+        let a = features(true, true, false, false);
+        let b = features(false, true, true, false);
+
+        assert_eq!(a.union(&b), features(true, true, true, false));
+        assert_eq!(a.intersection(&b), features(false, true, false, false));
+        assert_eq!(a.union(&DeviceFeatures::default()), a);
+        assert_eq!(
+            a.intersection(&DeviceFeatures::default()),
+            DeviceFeatures::default()
+        );
+    }
+}
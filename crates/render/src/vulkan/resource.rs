@@ -0,0 +1,255 @@
+use ash::vk;
+
+use super::{Device, Error, Result};
+
+/// A 2D image, either with its own dedicated memory or owned by a swapchain.
+pub struct Image {
+    pub(super) handle: vk::Image,
+    /// `None` for swapchain images, which are destroyed with their swapchain.
+    memory: Option<vk::DeviceMemory>,
+    extent: vk::Extent2D,
+    format: vk::Format,
+    device: Device,
+}
+
+impl Image {
+    pub fn new(
+        device: &Device,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Self> {
+        let create_info = vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            format,
+            extent: vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            samples: vk::SampleCountFlags::TYPE_1,
+            tiling: vk::ImageTiling::OPTIMAL,
+            usage,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            ..Default::default()
+        };
+
+        let raw = &device.inner.handle;
+        let handle = unsafe { raw.create_image(&create_info, None)? };
+        let requirements = unsafe { raw.get_image_memory_requirements(handle) };
+        let memory = match allocate(device, requirements, vk::MemoryPropertyFlags::DEVICE_LOCAL) {
+            Ok(memory) => memory,
+            Err(e) => {
+                unsafe { raw.destroy_image(handle, None) };
+                return Err(e);
+            }
+        };
+        if let Err(e) = unsafe { raw.bind_image_memory(handle, memory, 0) } {
+            unsafe {
+                raw.destroy_image(handle, None);
+                raw.free_memory(memory, None);
+            }
+            return Err(e.into());
+        }
+
+        Ok(Self {
+            handle,
+            memory: Some(memory),
+            extent,
+            format,
+            device: device.clone(),
+        })
+    }
+
+    pub(super) fn from_swapchain(
+        device: &Device,
+        handle: vk::Image,
+        extent: vk::Extent2D,
+        format: vk::Format,
+    ) -> Self {
+        Self {
+            handle,
+            memory: None,
+            extent,
+            format,
+            device: device.clone(),
+        }
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.device.set_object_name(self.handle, name)
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    pub fn format(&self) -> vk::Format {
+        self.format
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        if let Some(memory) = self.memory {
+            unsafe {
+                self.device.inner.handle.destroy_image(self.handle, None);
+                self.device.inner.handle.free_memory(memory, None);
+            }
+        }
+    }
+}
+
+/// A color view of the whole of an [`Image`].
+pub struct ImageView {
+    pub(super) handle: vk::ImageView,
+    device: Device,
+}
+
+impl ImageView {
+    pub fn new(image: &Image) -> Result<Self> {
+        let create_info = vk::ImageViewCreateInfo {
+            image: image.handle,
+            view_type: vk::ImageViewType::TYPE_2D,
+            format: image.format,
+            subresource_range: vk::ImageSubresourceRange {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                base_mip_level: 0,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            ..Default::default()
+        };
+
+        let handle = unsafe {
+            image
+                .device
+                .inner
+                .handle
+                .create_image_view(&create_info, None)?
+        };
+
+        Ok(Self {
+            handle,
+            device: image.device.clone(),
+        })
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.device.set_object_name(self.handle, name)
+    }
+}
+
+impl Drop for ImageView {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .inner
+                .handle
+                .destroy_image_view(self.handle, None)
+        };
+    }
+}
+
+/// A buffer with its own dedicated memory.
+pub struct Buffer {
+    pub(super) handle: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: u64,
+    device: Device,
+}
+
+impl Buffer {
+    pub fn new(
+        device: &Device,
+        size: u64,
+        usage: vk::BufferUsageFlags,
+        memory_flags: vk::MemoryPropertyFlags,
+    ) -> Result<Self> {
+        let create_info = vk::BufferCreateInfo {
+            size,
+            usage,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        };
+
+        let raw = &device.inner.handle;
+        let handle = unsafe { raw.create_buffer(&create_info, None)? };
+        let requirements = unsafe { raw.get_buffer_memory_requirements(handle) };
+        let memory = match allocate(device, requirements, memory_flags) {
+            Ok(memory) => memory,
+            Err(e) => {
+                unsafe { raw.destroy_buffer(handle, None) };
+                return Err(e);
+            }
+        };
+        if let Err(e) = unsafe { raw.bind_buffer_memory(handle, memory, 0) } {
+            unsafe {
+                raw.destroy_buffer(handle, None);
+                raw.free_memory(memory, None);
+            }
+            return Err(e.into());
+        }
+
+        Ok(Self {
+            handle,
+            memory,
+            size,
+            device: device.clone(),
+        })
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.device.set_object_name(self.handle, name)
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Copies the contents out of the buffer. The buffer must have been created
+    /// with `HOST_VISIBLE | HOST_COHERENT` memory.
+    pub fn read(&self) -> Result<Vec<u8>> {
+        let raw = &self.device.inner.handle;
+        let len = self.size as usize;
+
+        unsafe {
+            let ptr = raw.map_memory(self.memory, 0, self.size, vk::MemoryMapFlags::empty())?;
+            let data = std::slice::from_raw_parts(ptr.cast::<u8>(), len).to_vec();
+            raw.unmap_memory(self.memory);
+
+            Ok(data)
+        }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.inner.handle.destroy_buffer(self.handle, None);
+            self.device.inner.handle.free_memory(self.memory, None);
+        }
+    }
+}
+
+fn allocate(
+    device: &Device,
+    requirements: vk::MemoryRequirements,
+    flags: vk::MemoryPropertyFlags,
+) -> Result<vk::DeviceMemory> {
+    let memory_type_index = device
+        .find_memory_type(requirements.memory_type_bits, flags)
+        .ok_or(Error::from(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY))?;
+
+    let allocate_info = vk::MemoryAllocateInfo {
+        allocation_size: requirements.size,
+        memory_type_index,
+        ..Default::default()
+    };
+
+    Ok(unsafe { device.inner.handle.allocate_memory(&allocate_info, None)? })
+}
@@ -0,0 +1,621 @@
+use ash::vk;
+
+use super::Extent2D;
+
+/// What a surface supports, as reported by
+/// [`PhysicalDevice::surface_capabilities`](super::PhysicalDevice::surface_capabilities).
+#[derive(Debug, Clone)]
+pub struct SurfaceCapabilitiesKhr {
+    min_image_count: u32,
+    max_image_count: Option<u32>,
+    current_extent: Option<Extent2D>,
+    min_image_extent: Extent2D,
+    max_image_extent: Extent2D,
+    max_image_array_layers: u32,
+    supported_transforms: SurfaceTransformKhr,
+    current_transform: SurfaceTransformKhr,
+    supported_composite_alpha: CompositeAlphaKhr,
+    supported_usage: ImageUsage,
+}
+
+impl SurfaceCapabilitiesKhr {
+    pub fn min_image_count(&self) -> u32 {
+        self.min_image_count
+    }
+
+    /// `None` if there is no limit on the number of images.
+    pub fn max_image_count(&self) -> Option<u32> {
+        self.max_image_count
+    }
+
+    /// `None` if the size of the surface is determined by the swapchain.
+    pub fn current_extent(&self) -> Option<Extent2D> {
+        self.current_extent
+    }
+
+    pub fn min_image_extent(&self) -> Extent2D {
+        self.min_image_extent
+    }
+
+    pub fn max_image_extent(&self) -> Extent2D {
+        self.max_image_extent
+    }
+
+    pub fn max_image_array_layers(&self) -> u32 {
+        self.max_image_array_layers
+    }
+
+    pub fn supported_transforms(&self) -> SurfaceTransformKhr {
+        self.supported_transforms
+    }
+
+    /// Has exactly one transform set.
+    pub fn current_transform(&self) -> SurfaceTransformKhr {
+        self.current_transform
+    }
+
+    pub fn supported_composite_alpha(&self) -> CompositeAlphaKhr {
+        self.supported_composite_alpha
+    }
+
+    pub fn supported_usage(&self) -> ImageUsage {
+        self.supported_usage
+    }
+}
+
+impl From<vk::SurfaceCapabilitiesKHR> for SurfaceCapabilitiesKhr {
+    fn from(caps: vk::SurfaceCapabilitiesKHR) -> Self {
+        Self {
+            min_image_count: caps.min_image_count,
+            max_image_count: (caps.max_image_count != 0).then_some(caps.max_image_count),
+            current_extent: (caps.current_extent.width != u32::MAX)
+                .then(|| caps.current_extent.into()),
+            min_image_extent: caps.min_image_extent.into(),
+            max_image_extent: caps.max_image_extent.into(),
+            max_image_array_layers: caps.max_image_array_layers,
+            supported_transforms: caps.supported_transforms.into(),
+            current_transform: caps.current_transform.into(),
+            supported_composite_alpha: caps.supported_composite_alpha.into(),
+            supported_usage: caps.supported_usage_flags.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceFormat {
+    format: Format,
+    color_space: ColorSpace,
+}
+
+impl SurfaceFormat {
+    pub fn new(format: Format, color_space: ColorSpace) -> Self {
+        Self {
+            format,
+            color_space,
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+}
+
+impl From<vk::SurfaceFormatKHR> for SurfaceFormat {
+    fn from(format: vk::SurfaceFormatKHR) -> Self {
+        Self {
+            format: format.format.into(),
+            color_space: format.color_space.into(),
+        }
+    }
+}
+
+impl From<SurfaceFormat> for vk::SurfaceFormatKHR {
+    fn from(format: SurfaceFormat) -> Self {
+        Self {
+            format: format.format.into(),
+            color_space: format.color_space.into(),
+        }
+    }
+}
+
+/// Image formats commonly offered by surfaces. Anything else is kept as its raw
+/// value in `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    B8G8R8A8Unorm,
+    B8G8R8A8Srgb,
+    R8G8B8A8Unorm,
+    R8G8B8A8Srgb,
+    A2R10G10B10UnormPack32,
+    A2B10G10R10UnormPack32,
+    R16G16B16A16Sfloat,
+    R5G6B5UnormPack16,
+    Other(i32),
+}
+
+impl From<vk::Format> for Format {
+    fn from(format: vk::Format) -> Self {
+        match format {
+            vk::Format::B8G8R8A8_UNORM => Self::B8G8R8A8Unorm,
+            vk::Format::B8G8R8A8_SRGB => Self::B8G8R8A8Srgb,
+            vk::Format::R8G8B8A8_UNORM => Self::R8G8B8A8Unorm,
+            vk::Format::R8G8B8A8_SRGB => Self::R8G8B8A8Srgb,
+            vk::Format::A2R10G10B10_UNORM_PACK32 => Self::A2R10G10B10UnormPack32,
+            vk::Format::A2B10G10R10_UNORM_PACK32 => Self::A2B10G10R10UnormPack32,
+            vk::Format::R16G16B16A16_SFLOAT => Self::R16G16B16A16Sfloat,
+            vk::Format::R5G6B5_UNORM_PACK16 => Self::R5G6B5UnormPack16,
+            other => Self::Other(other.as_raw()),
+        }
+    }
+}
+
+impl From<Format> for vk::Format {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::B8G8R8A8Unorm => Self::B8G8R8A8_UNORM,
+            Format::B8G8R8A8Srgb => Self::B8G8R8A8_SRGB,
+            Format::R8G8B8A8Unorm => Self::R8G8B8A8_UNORM,
+            Format::R8G8B8A8Srgb => Self::R8G8B8A8_SRGB,
+            Format::A2R10G10B10UnormPack32 => Self::A2R10G10B10_UNORM_PACK32,
+            Format::A2B10G10R10UnormPack32 => Self::A2B10G10R10_UNORM_PACK32,
+            Format::R16G16B16A16Sfloat => Self::R16G16B16A16_SFLOAT,
+            Format::R5G6B5UnormPack16 => Self::R5G6B5_UNORM_PACK16,
+            Format::Other(raw) => Self::from_raw(raw),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    SrgbNonlinear,
+    DisplayP3Nonlinear,
+    ExtendedSrgbLinear,
+    Hdr10St2084,
+    Other(i32),
+}
+
+impl From<vk::ColorSpaceKHR> for ColorSpace {
+    fn from(color_space: vk::ColorSpaceKHR) -> Self {
+        match color_space {
+            vk::ColorSpaceKHR::SRGB_NONLINEAR => Self::SrgbNonlinear,
+            vk::ColorSpaceKHR::DISPLAY_P3_NONLINEAR_EXT => Self::DisplayP3Nonlinear,
+            vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => Self::ExtendedSrgbLinear,
+            vk::ColorSpaceKHR::HDR10_ST2084_EXT => Self::Hdr10St2084,
+            other => Self::Other(other.as_raw()),
+        }
+    }
+}
+
+impl From<ColorSpace> for vk::ColorSpaceKHR {
+    fn from(color_space: ColorSpace) -> Self {
+        match color_space {
+            ColorSpace::SrgbNonlinear => Self::SRGB_NONLINEAR,
+            ColorSpace::DisplayP3Nonlinear => Self::DISPLAY_P3_NONLINEAR_EXT,
+            ColorSpace::ExtendedSrgbLinear => Self::EXTENDED_SRGB_LINEAR_EXT,
+            ColorSpace::Hdr10St2084 => Self::HDR10_ST2084_EXT,
+            ColorSpace::Other(raw) => Self::from_raw(raw),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Images are shown right away, which may tear.
+    Immediate,
+    /// The newest image replaces the queued one; does not tear.
+    Mailbox,
+    /// Waits for vertical blank. Always supported.
+    Fifo,
+    /// Like `Fifo`, but a late image is shown right away and may tear.
+    FifoRelaxed,
+    SharedDemandRefresh,
+    SharedContinuousRefresh,
+    Other(i32),
+}
+
+impl From<vk::PresentModeKHR> for PresentMode {
+    fn from(mode: vk::PresentModeKHR) -> Self {
+        match mode {
+            vk::PresentModeKHR::IMMEDIATE => Self::Immediate,
+            vk::PresentModeKHR::MAILBOX => Self::Mailbox,
+            vk::PresentModeKHR::FIFO => Self::Fifo,
+            vk::PresentModeKHR::FIFO_RELAXED => Self::FifoRelaxed,
+            vk::PresentModeKHR::SHARED_DEMAND_REFRESH => Self::SharedDemandRefresh,
+            vk::PresentModeKHR::SHARED_CONTINUOUS_REFRESH => Self::SharedContinuousRefresh,
+            other => Self::Other(other.as_raw()),
+        }
+    }
+}
+
+impl From<PresentMode> for vk::PresentModeKHR {
+    fn from(mode: PresentMode) -> Self {
+        match mode {
+            PresentMode::Immediate => Self::IMMEDIATE,
+            PresentMode::Mailbox => Self::MAILBOX,
+            PresentMode::Fifo => Self::FIFO,
+            PresentMode::FifoRelaxed => Self::FIFO_RELAXED,
+            PresentMode::SharedDemandRefresh => Self::SHARED_DEMAND_REFRESH,
+            PresentMode::SharedContinuousRefresh => Self::SHARED_CONTINUOUS_REFRESH,
+            PresentMode::Other(raw) => Self::from_raw(raw),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompositeAlphaKhr {
+    opaque: bool,
+    pre_multiplied: bool,
+    post_multiplied: bool,
+    inherit: bool,
+}
+
+impl CompositeAlphaKhr {
+    pub fn opaque(&self) -> bool {
+        self.opaque
+    }
+
+    pub fn pre_multiplied(&self) -> bool {
+        self.pre_multiplied
+    }
+
+    pub fn post_multiplied(&self) -> bool {
+        self.post_multiplied
+    }
+
+    pub fn inherit(&self) -> bool {
+        self.inherit
+    }
+}
+
+impl From<vk::CompositeAlphaFlagsKHR> for CompositeAlphaKhr {
+    fn from(flags: vk::CompositeAlphaFlagsKHR) -> Self {
+        Self {
+            opaque: flags.contains(vk::CompositeAlphaFlagsKHR::OPAQUE),
+            pre_multiplied: flags.contains(vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED),
+            post_multiplied: flags.contains(vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED),
+            inherit: flags.contains(vk::CompositeAlphaFlagsKHR::INHERIT),
+        }
+    }
+}
+
+impl From<CompositeAlphaKhr> for vk::CompositeAlphaFlagsKHR {
+    fn from(alpha: CompositeAlphaKhr) -> Self {
+        let mut flags = Self::empty();
+        if alpha.opaque {
+            flags |= Self::OPAQUE;
+        }
+        if alpha.pre_multiplied {
+            flags |= Self::PRE_MULTIPLIED;
+        }
+        if alpha.post_multiplied {
+            flags |= Self::POST_MULTIPLIED;
+        }
+        if alpha.inherit {
+            flags |= Self::INHERIT;
+        }
+        flags
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageUsage {
+    transfer_src: bool,
+    transfer_dst: bool,
+    sampled: bool,
+    storage: bool,
+    color_attachment: bool,
+    depth_stencil_attachment: bool,
+    transient_attachment: bool,
+    input_attachment: bool,
+}
+
+impl ImageUsage {
+    pub fn transfer_src(&self) -> bool {
+        self.transfer_src
+    }
+
+    pub fn transfer_dst(&self) -> bool {
+        self.transfer_dst
+    }
+
+    pub fn sampled(&self) -> bool {
+        self.sampled
+    }
+
+    pub fn storage(&self) -> bool {
+        self.storage
+    }
+
+    pub fn color_attachment(&self) -> bool {
+        self.color_attachment
+    }
+
+    pub fn depth_stencil_attachment(&self) -> bool {
+        self.depth_stencil_attachment
+    }
+
+    pub fn transient_attachment(&self) -> bool {
+        self.transient_attachment
+    }
+
+    pub fn input_attachment(&self) -> bool {
+        self.input_attachment
+    }
+}
+
+impl From<vk::ImageUsageFlags> for ImageUsage {
+    fn from(flags: vk::ImageUsageFlags) -> Self {
+        Self {
+            transfer_src: flags.contains(vk::ImageUsageFlags::TRANSFER_SRC),
+            transfer_dst: flags.contains(vk::ImageUsageFlags::TRANSFER_DST),
+            sampled: flags.contains(vk::ImageUsageFlags::SAMPLED),
+            storage: flags.contains(vk::ImageUsageFlags::STORAGE),
+            color_attachment: flags.contains(vk::ImageUsageFlags::COLOR_ATTACHMENT),
+            depth_stencil_attachment: flags.contains(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT),
+            transient_attachment: flags.contains(vk::ImageUsageFlags::TRANSIENT_ATTACHMENT),
+            input_attachment: flags.contains(vk::ImageUsageFlags::INPUT_ATTACHMENT),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SurfaceTransformKhr {
+    identity: bool,
+    rotate_90: bool,
+    rotate_180: bool,
+    rotate_270: bool,
+    horizontal_mirror: bool,
+    horizontal_mirror_rotate_90: bool,
+    horizontal_mirror_rotate_180: bool,
+    horizontal_mirror_rotate_270: bool,
+    inherit: bool,
+}
+
+impl SurfaceTransformKhr {
+    pub fn identity(&self) -> bool {
+        self.identity
+    }
+
+    pub fn rotate_90(&self) -> bool {
+        self.rotate_90
+    }
+
+    pub fn rotate_180(&self) -> bool {
+        self.rotate_180
+    }
+
+    pub fn rotate_270(&self) -> bool {
+        self.rotate_270
+    }
+
+    pub fn horizontal_mirror(&self) -> bool {
+        self.horizontal_mirror
+    }
+
+    pub fn horizontal_mirror_rotate_90(&self) -> bool {
+        self.horizontal_mirror_rotate_90
+    }
+
+    pub fn horizontal_mirror_rotate_180(&self) -> bool {
+        self.horizontal_mirror_rotate_180
+    }
+
+    pub fn horizontal_mirror_rotate_270(&self) -> bool {
+        self.horizontal_mirror_rotate_270
+    }
+
+    pub fn inherit(&self) -> bool {
+        self.inherit
+    }
+}
+
+impl From<vk::SurfaceTransformFlagsKHR> for SurfaceTransformKhr {
+    fn from(flags: vk::SurfaceTransformFlagsKHR) -> Self {
+        use vk::SurfaceTransformFlagsKHR as F;
+
+        Self {
+            identity: flags.contains(F::IDENTITY),
+            rotate_90: flags.contains(F::ROTATE_90),
+            rotate_180: flags.contains(F::ROTATE_180),
+            rotate_270: flags.contains(F::ROTATE_270),
+            horizontal_mirror: flags.contains(F::HORIZONTAL_MIRROR),
+            horizontal_mirror_rotate_90: flags.contains(F::HORIZONTAL_MIRROR_ROTATE_90),
+            horizontal_mirror_rotate_180: flags.contains(F::HORIZONTAL_MIRROR_ROTATE_180),
+            horizontal_mirror_rotate_270: flags.contains(F::HORIZONTAL_MIRROR_ROTATE_270),
+            inherit: flags.contains(F::INHERIT),
+        }
+    }
+}
+
+impl From<SurfaceTransformKhr> for vk::SurfaceTransformFlagsKHR {
+    fn from(transform: SurfaceTransformKhr) -> Self {
+        let mut flags = Self::empty();
+        if transform.identity {
+            flags |= Self::IDENTITY;
+        }
+        if transform.rotate_90 {
+            flags |= Self::ROTATE_90;
+        }
+        if transform.rotate_180 {
+            flags |= Self::ROTATE_180;
+        }
+        if transform.rotate_270 {
+            flags |= Self::ROTATE_270;
+        }
+        if transform.horizontal_mirror {
+            flags |= Self::HORIZONTAL_MIRROR;
+        }
+        if transform.horizontal_mirror_rotate_90 {
+            flags |= Self::HORIZONTAL_MIRROR_ROTATE_90;
+        }
+        if transform.horizontal_mirror_rotate_180 {
+            flags |= Self::HORIZONTAL_MIRROR_ROTATE_180;
+        }
+        if transform.horizontal_mirror_rotate_270 {
+            flags |= Self::HORIZONTAL_MIRROR_ROTATE_270;
+        }
+        if transform.inherit {
+            flags |= Self::INHERIT;
+        }
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_round_trip() {
+        for raw in [
+            vk::Format::B8G8R8A8_UNORM,
+            vk::Format::B8G8R8A8_SRGB,
+            vk::Format::R8G8B8A8_UNORM,
+            vk::Format::R8G8B8A8_SRGB,
+            vk::Format::A2R10G10B10_UNORM_PACK32,
+            vk::Format::A2B10G10R10_UNORM_PACK32,
+            vk::Format::R16G16B16A16_SFLOAT,
+            vk::Format::R5G6B5_UNORM_PACK16,
+            vk::Format::BC7_SRGB_BLOCK,
+        ] {
+            assert_eq!(vk::Format::from(Format::from(raw)).as_raw(), raw.as_raw());
+        }
+        for raw in [
+            vk::ColorSpaceKHR::SRGB_NONLINEAR,
+            vk::ColorSpaceKHR::DISPLAY_P3_NONLINEAR_EXT,
+            vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+            vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+            vk::ColorSpaceKHR::BT709_LINEAR_EXT,
+        ] {
+            assert_eq!(
+                vk::ColorSpaceKHR::from(ColorSpace::from(raw)).as_raw(),
+                raw.as_raw()
+            );
+        }
+
+        let raw = vk::SurfaceFormatKHR {
+            format: vk::Format::B8G8R8A8_SRGB,
+            color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        };
+        let format = SurfaceFormat::from(raw);
+        assert_eq!(
+            format,
+            SurfaceFormat::new(Format::B8G8R8A8Srgb, ColorSpace::SrgbNonlinear)
+        );
+        let back = vk::SurfaceFormatKHR::from(format);
+        assert_eq!(back.format.as_raw(), raw.format.as_raw());
+        assert_eq!(back.color_space.as_raw(), raw.color_space.as_raw());
+    }
+
+    #[test]
+    fn present_modes_round_trip() {
+        for raw in [
+            vk::PresentModeKHR::IMMEDIATE,
+            vk::PresentModeKHR::MAILBOX,
+            vk::PresentModeKHR::FIFO,
+            vk::PresentModeKHR::FIFO_RELAXED,
+            vk::PresentModeKHR::SHARED_DEMAND_REFRESH,
+            vk::PresentModeKHR::SHARED_CONTINUOUS_REFRESH,
+        ] {
+            assert_eq!(
+                vk::PresentModeKHR::from(PresentMode::from(raw)).as_raw(),
+                raw.as_raw()
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_values() {
+        assert_eq!(
+            Format::from(vk::Format::from_raw(1_000_999_000)),
+            Format::Other(1_000_999_000)
+        );
+        assert_eq!(
+            ColorSpace::from(vk::ColorSpaceKHR::from_raw(-1)),
+            ColorSpace::Other(-1)
+        );
+        assert_eq!(
+            PresentMode::from(vk::PresentModeKHR::from_raw(1_000_999_000)),
+            PresentMode::Other(1_000_999_000)
+        );
+        assert_eq!(
+            vk::PresentModeKHR::from(PresentMode::Other(1_000_999_000)).as_raw(),
+            1_000_999_000
+        );
+    }
+
+    #[test]
+    fn flags_round_trip() {
+        let alpha = vk::CompositeAlphaFlagsKHR::OPAQUE | vk::CompositeAlphaFlagsKHR::INHERIT;
+        let typed = CompositeAlphaKhr::from(alpha);
+        assert!(typed.opaque() && typed.inherit());
+        assert!(!typed.pre_multiplied() && !typed.post_multiplied());
+        assert_eq!(
+            vk::CompositeAlphaFlagsKHR::from(typed).as_raw(),
+            alpha.as_raw()
+        );
+
+        let transforms = vk::SurfaceTransformFlagsKHR::IDENTITY
+            | vk::SurfaceTransformFlagsKHR::ROTATE_270
+            | vk::SurfaceTransformFlagsKHR::HORIZONTAL_MIRROR_ROTATE_90;
+        let typed = SurfaceTransformKhr::from(transforms);
+        assert!(typed.identity() && typed.rotate_270() && typed.horizontal_mirror_rotate_90());
+        assert!(!typed.rotate_90() && !typed.inherit());
+        assert_eq!(
+            vk::SurfaceTransformFlagsKHR::from(typed).as_raw(),
+            transforms.as_raw()
+        );
+    }
+
+    #[test]
+    fn reads_capabilities() {
+        let raw = vk::SurfaceCapabilitiesKHR {
+            min_image_count: 2,
+            max_image_count: 0,
+            current_extent: vk::Extent2D {
+                width: u32::MAX,
+                height: u32::MAX,
+            },
+            min_image_extent: vk::Extent2D {
+                width: 1,
+                height: 1,
+            },
+            max_image_extent: vk::Extent2D {
+                width: 4096,
+                height: 2048,
+            },
+            max_image_array_layers: 1,
+            supported_transforms: vk::SurfaceTransformFlagsKHR::IDENTITY,
+            current_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
+            supported_composite_alpha: vk::CompositeAlphaFlagsKHR::OPAQUE,
+            supported_usage_flags: vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::TRANSFER_DST,
+        };
+        let caps = SurfaceCapabilitiesKhr::from(raw);
+
+        assert_eq!(caps.min_image_count(), 2);
+        assert_eq!(caps.max_image_count(), None);
+        assert_eq!(caps.current_extent(), None);
+        assert_eq!(caps.max_image_extent(), Extent2D::new(4096, 2048));
+        assert!(caps.current_transform().identity());
+        assert!(caps.supported_composite_alpha().opaque());
+        assert!(caps.supported_usage().color_attachment());
+        assert!(caps.supported_usage().transfer_dst());
+        assert!(!caps.supported_usage().sampled());
+
+        let caps = SurfaceCapabilitiesKhr::from(vk::SurfaceCapabilitiesKHR {
+            max_image_count: 3,
+            current_extent: vk::Extent2D {
+                width: 800,
+                height: 600,
+            },
+            ..raw
+        });
+        assert_eq!(caps.max_image_count(), Some(3));
+        assert_eq!(caps.current_extent(), Some(Extent2D::new(800, 600)));
+    }
+}
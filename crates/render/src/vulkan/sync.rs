@@ -0,0 +1,86 @@
+use ash::vk;
+
+use super::{Device, Result};
+
+pub struct Fence {
+    pub(super) handle: vk::Fence,
+    device: Device,
+}
+
+impl Fence {
+    pub fn new(device: &Device, signaled: bool) -> Result<Self> {
+        let create_info = vk::FenceCreateInfo {
+            flags: if signaled {
+                vk::FenceCreateFlags::SIGNALED
+            } else {
+                vk::FenceCreateFlags::empty()
+            },
+            ..Default::default()
+        };
+
+        let handle = unsafe { device.inner.handle.create_fence(&create_info, None)? };
+
+        Ok(Self {
+            handle,
+            device: device.clone(),
+        })
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.device.set_object_name(self.handle, name)
+    }
+
+    /// Blocks until the fence is signaled.
+    pub fn wait(&self) -> Result<()> {
+        unsafe {
+            self.device
+                .inner
+                .handle
+                .wait_for_fences(&[self.handle], true, u64::MAX)?
+        };
+        Ok(())
+    }
+
+    pub fn reset(&self) -> Result<()> {
+        unsafe { self.device.inner.handle.reset_fences(&[self.handle])? };
+        Ok(())
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe { self.device.inner.handle.destroy_fence(self.handle, None) };
+    }
+}
+
+pub struct Semaphore {
+    pub(super) handle: vk::Semaphore,
+    device: Device,
+}
+
+impl Semaphore {
+    pub fn new(device: &Device) -> Result<Self> {
+        let create_info = vk::SemaphoreCreateInfo::default();
+        let handle = unsafe { device.inner.handle.create_semaphore(&create_info, None)? };
+
+        Ok(Self {
+            handle,
+            device: device.clone(),
+        })
+    }
+
+    pub fn set_debug_name(&self, name: &str) -> Result<()> {
+        self.device.set_object_name(self.handle, name)
+    }
+}
+
+impl Drop for Semaphore {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .inner
+                .handle
+                .destroy_semaphore(self.handle, None)
+        };
+    }
+}
@@ -0,0 +1,83 @@
+use render::{Error, Queue, QueueRole, Renderer, RendererConfig};
+
+/// Makes the tests fail instead of skipping when there is no Vulkan, so that a
+/// CI job with lavapipe proves they ran.
+const REQUIRE_VULKAN_ENV_VAR: &str = "TANELORN_REQUIRE_VULKAN";
+
+/// A headless renderer, or `None` if this machine has no Vulkan loader or
+/// device, e.g. a CI runner without lavapipe.
+fn headless(width: u32, height: u32) -> Option<Renderer> {
+    let config = RendererConfig::new().panic_on_validation_error(true);
+    match Renderer::new_headless_with_config(width, height, config) {
+        Ok(renderer) => Some(renderer),
+        Err(e @ (Error::Loader(_) | Error::NoPhysicalDevice)) => {
+            if std::env::var_os(REQUIRE_VULKAN_ENV_VAR).is_some_and(|v| !v.is_empty()) {
+                panic!("Vulkan is required by {}: {}", REQUIRE_VULKAN_ENV_VAR, e);
+            }
+            eprintln!("Skipping, no Vulkan device available: {}", e);
+            None
+        }
+        Err(e) => panic!("Could not create renderer: {}", e),
+    }
+}
+
+#[test]
+fn clears_every_pixel() {
+    let (width, height) = (64, 48);
+    let Some(mut renderer) = headless(width, height) else {
+        return;
+    };
+
+    renderer.set_clear_color([1.0, 0.0, 1.0, 1.0]);
+    renderer.draw_frame().unwrap();
+    let pixels = renderer.read_pixels().unwrap().unwrap();
+
+    assert_eq!(renderer.extent(), (width, height));
+    assert_eq!(pixels.len(), (width * height * 4) as usize);
+    for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+        assert_eq!(pixel, [255, 0, 255, 255], "pixel {}", i);
+    }
+}
+
+#[test]
+fn resize_to_zero_keeps_image() {
+    let Some(mut renderer) = headless(16, 16) else {
+        return;
+    };
+
+    renderer.resize(0, 0).unwrap();
+    renderer.resize(16, 0).unwrap();
+    renderer.draw_frame().unwrap();
+
+    assert_eq!(renderer.extent(), (16, 16));
+    assert_eq!(renderer.read_pixels().unwrap().unwrap().len(), 16 * 16 * 4);
+}
+
+#[test]
+fn exposes_queues_by_role() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Queue>();
+
+    let Some(renderer) = headless(16, 16) else {
+        return;
+    };
+
+    for role in [QueueRole::Graphics, QueueRole::Compute, QueueRole::Transfer] {
+        assert!(renderer.queue(role).is_some(), "no {:?} queue", role);
+    }
+    assert!(renderer.queue(QueueRole::Present).is_none());
+}
+
+#[test]
+fn rejects_empty_image() {
+    for (width, height) in [(0, 16), (16, 0), (0, 0)] {
+        match Renderer::new_headless(width, height) {
+            Err(Error::InvalidExtent {
+                width: w,
+                height: h,
+            }) => assert_eq!((w, h), (width, height)),
+            Err(e) => panic!("Expected an invalid extent error, got {}", e),
+            Ok(_) => panic!("Created a {}x{} renderer", width, height),
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use render::Renderer;
+use hot_reload::HotModule;
+use render::{Error, Renderer};
 
 use winit::dpi::PhysicalSize;
 use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
@@ -13,29 +14,80 @@ fn main() {
     let window = WindowBuilder::new()
         .with_title("Vulkan Tutorial")
         .with_inner_size(PhysicalSize::new(800, 600))
-        .with_resizable(false)
         .build(&event_loop)
         .unwrap();
 
-    let _renderer = Renderer::new(&window);
+    let size = window.inner_size();
+    let mut renderer = match Renderer::new(&window, size.width, size.height) {
+        Ok(renderer) => renderer,
+        Err(
+            e @ (Error::NoPhysicalDevice
+            | Error::DeviceNotFound(_)
+            | Error::MissingDeviceRequirements { .. }),
+        ) => {
+            log::error!("Could not find a compatible GPU: {}", e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut game_module =
+        std::env::var_os("TANELORN_GAME_MODULE").and_then(|path| match HotModule::load(&path) {
+            Ok(module) => Some(module),
+            Err(e) => {
+                log::error!("Could not load game module: {}", e);
+                None
+            }
+        });
 
     event_loop.run_return(|event, _, control_flow| {
         control_flow.set_poll();
 
         match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested
-                | WindowEvent::KeyboardInput {
-                    input:
-                        KeyboardInput {
-                            state: ElementState::Pressed,
-                            virtual_keycode: Some(VirtualKeyCode::Escape),
-                            ..
-                        },
-                    ..
-                } => control_flow.set_exit(),
-                _ => (),
-            },
+            Event::WindowEvent {
+                event:
+                    WindowEvent::CloseRequested
+                    | WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => control_flow.set_exit(),
+            Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                if let Err(e) = renderer.resize(size.width, size.height) {
+                    log::error!("Could not resize: {}", e);
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ScaleFactorChanged { new_inner_size, .. },
+                ..
+            } => {
+                if let Err(e) = renderer.resize(new_inner_size.width, new_inner_size.height) {
+                    log::error!("Could not resize: {}", e);
+                }
+            }
+            Event::MainEventsCleared => {
+                if let Some(game_module) = game_module.as_mut() {
+                    if let Err(e) = game_module.reload_if_changed() {
+                        log::error!("Could not reload game module: {}", e);
+                    }
+                    game_module.update();
+                }
+                if let Err(e) = renderer.draw_frame() {
+                    log::error!("Could not draw frame: {}", e);
+                    control_flow.set_exit();
+                }
+            }
             _ => (),
         }
     });